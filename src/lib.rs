@@ -1,28 +1,94 @@
+#[cfg(any(feature = "tetra", feature = "terminal", feature = "web"))]
 use std::sync::mpsc::Receiver;
 
 use rand::Rng;
 use robotics_lib::world::world_generator::Generator;
+#[cfg(feature = "tetra")]
 use tetra::ContextBuilder;
 
-use crate::visualizer::{VisData, Visualizer};
+#[cfg(feature = "tetra")]
+use crate::transport::{Endpoint, LinkStatus};
+#[cfg(any(feature = "tetra", feature = "terminal", feature = "web"))]
+use crate::visualizer::VisData;
+#[cfg(feature = "tetra")]
+use crate::visualizer::Visualizer;
+#[cfg(feature = "tetra")]
+use crate::visualizer::config::VisConfig;
 
 pub mod visualizer;
+pub mod transport;
+#[cfg(feature = "web")]
+pub mod web;
 
 pub const WINDOW_WIDTH: i32 = 800;
 pub const WINDOW_HEIGHT: i32 = 800;
 
 ///Builds and runs the window
+#[cfg(feature = "tetra")]
 pub fn start(vis_receiver: Receiver<VisData>, size: usize) {
+    start_with_config(vis_receiver, size, VisConfig::default());
+}
+
+///Builds and runs the window with the given options
+#[cfg(feature = "tetra")]
+pub fn start_with_config(vis_receiver: Receiver<VisData>, size: usize, config: VisConfig) {
+    run_window(vis_receiver, None, size, config);
+}
+
+///Builds and runs the window, writing the connection news of 'link_status' in the console
+#[cfg(feature = "tetra")]
+fn run_window(vis_receiver: Receiver<VisData>, link_status: Option<Receiver<LinkStatus>>, size: usize, config: VisConfig) {
     let mut c = ContextBuilder::new("tyrannosauRUST-rex | Chi dorme non piglia pesci", WINDOW_WIDTH, WINDOW_HEIGHT)
         .show_mouse(true)
         .quit_on_escape(true)
         .resizable(true)
         .build().expect("failed to build context");
 
-    let vis = Visualizer::new(&mut c, size, vis_receiver)
+    let mut vis = Visualizer::with_config(&mut c, size, vis_receiver, config)
         .expect("failed to create visualizer");
+    if let Some(status) = link_status {
+        vis = vis.with_link_status(status);
+    }
 
     c.run(|_ctx| {
         Ok(vis)
     }).expect("failed to run");
-}
\ No newline at end of file
+}
+
+///Runs the window in listener mode, receiving the updates from robots
+///running in other processes (see [transport::VisSender])
+#[cfg(feature = "tetra")]
+pub fn start_listening(endpoint: Endpoint, size: usize) {
+    let (vis_receiver, link_status) = transport::listen_with_status(endpoint)
+        .expect("failed to listen for robot updates");
+    run_window(vis_receiver, Some(link_status), size, VisConfig::default());
+}
+
+///Runs the terminal (ANSI) visualizer, useful over SSH with no display
+#[cfg(feature = "terminal")]
+pub fn start_terminal(vis_receiver: Receiver<VisData>, size: usize) {
+    visualizer::visterminal::VisTerminal::new(size, vis_receiver)
+        .run()
+        .expect("failed to run terminal visualizer");
+}
+
+///Runs the browser viewer only, serving the page and its websocket on 'addr'
+#[cfg(feature = "web")]
+pub fn start_web(vis_receiver: Receiver<VisData>, size: usize, addr: &str) {
+    web::serve(vis_receiver, size, addr).expect("failed to run web viewer");
+}
+
+///Runs the window and the browser viewer on 'addr' together, both showing every update
+#[cfg(all(feature = "tetra", feature = "web"))]
+pub fn start_with_web(vis_receiver: Receiver<VisData>, size: usize, addr: &str, config: VisConfig) {
+    let viewer = web::WebViewer::bind(addr, size).expect("failed to run web viewer");
+    let mut receivers = transport::broadcast(vis_receiver, 2);
+    let window_receiver = receivers.pop().expect("one receiver per front end");
+    let web_receiver = receivers.pop().expect("one receiver per front end");
+    std::thread::spawn(move || {
+        for data in web_receiver.iter() {
+            viewer.apply(data);
+        }
+    });
+    run_window(window_receiver, None, size, config);
+}
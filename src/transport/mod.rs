@@ -0,0 +1,211 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::transport::wire::WireData;
+use crate::visualizer::VisData;
+
+pub mod wire;
+
+///where the visualizer listens and the robot connects
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    ///"host:port" address
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    ///parses "unix:/path/to.sock" as a unix socket, anything else as a tcp address
+    pub fn parse(s: &str) -> Self {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Endpoint::Unix(PathBuf::from(path));
+        }
+        Endpoint::Tcp(s.to_string())
+    }
+    fn connect(&self) -> io::Result<Box<dyn Write + Send>> {
+        match self {
+            Endpoint::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => Ok(Box::new(UnixStream::connect(path)?)),
+        }
+    }
+}
+
+///robot side of the transport, sends [VisData] to a listening visualizer
+///
+///the connection is (re)opened lazily, so the robot keeps running
+///when no visualizer is attached
+pub struct VisSender {
+    endpoint: Endpoint,
+    stream: Option<Box<dyn Write + Send>>,
+}
+
+impl VisSender {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            stream: None,
+        }
+    }
+    ///creates a sender and connects it right away
+    pub fn connect(endpoint: Endpoint) -> io::Result<Self> {
+        let stream = endpoint.connect()?;
+        Ok(Self {
+            endpoint,
+            stream: Some(stream),
+        })
+    }
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+    ///sends 'data', connecting first if needed
+    ///
+    ///on failure the connection is dropped and retried on the next call
+    pub fn send(&mut self, data: &VisData) -> io::Result<()> {
+        let mut line = WireData::from(data).to_line()?;
+        line.push('\n');
+
+        if self.stream.is_none() {
+            self.stream = Some(self.endpoint.connect()?);
+        }
+        let res = self.stream.as_mut()
+            .map_or(Ok(()), |s| s.write_all(line.as_bytes()).and_then(|_| s.flush()));
+        if res.is_err() {
+            self.stream = None;
+        }
+        res
+    }
+}
+
+///what happened to the robot connections, see [listen_with_status]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkStatus {
+    Connected,
+    Disconnected,
+    ///a line that isn't an update, with the reason
+    Malformed(String),
+    ///accepting a connection failed
+    AcceptFailed(String),
+}
+
+///visualizer side of the transport
+///
+///binds 'endpoint' and forwards every received update to the returned
+///receiver, robots can connect at the same time and reconnect at any time
+pub fn listen(endpoint: Endpoint) -> io::Result<Receiver<VisData>> {
+    listen_with_status(endpoint).map(|(updates, _)| updates)
+}
+
+///as [listen], also reporting connections, disconnections and errors on the second receiver
+///
+///a unix socket path is only replaced if it is a stale socket, any other file makes it fail
+pub fn listen_with_status(endpoint: Endpoint) -> io::Result<(Receiver<VisData>, Receiver<LinkStatus>)> {
+    let (tx, rx) = mpsc::channel();
+    let (status_tx, status_rx) = mpsc::channel();
+    match endpoint {
+        Endpoint::Tcp(addr) => {
+            let listener = TcpListener::bind(addr)?;
+            thread::spawn(move || accept(listener.incoming(), tx, status_tx));
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(path) => {
+            remove_stale_socket(&path)?;
+            let listener = UnixListener::bind(path)?;
+            thread::spawn(move || accept(listener.incoming(), tx, status_tx));
+        }
+    }
+    Ok((rx, status_rx))
+}
+
+///copies every update of 'vis_receiver' to 'count' receivers, so several front ends
+///(e.g. the window and the web viewer) can show the same robots
+///
+///a receiver that is dropped stops getting updates, the others keep going
+pub fn broadcast(vis_receiver: Receiver<VisData>, count: usize) -> Vec<Receiver<VisData>> {
+    let (mut senders, receivers): (Vec<Sender<VisData>>, Vec<Receiver<VisData>>) = (0..count).map(|_| mpsc::channel()).unzip();
+    thread::spawn(move || {
+        for data in vis_receiver.iter() {
+            senders.retain(|tx| tx.send(data.clone()).is_ok());
+            if senders.is_empty() {
+                break;
+            }
+        }
+    });
+    receivers
+}
+
+///a socket file left by a previous run would make bind fail, anything else is not ours to delete
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(io::ErrorKind::AlreadyExists,
+                                    format!("{} exists and is not a socket", path.display()))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+///accepts the robot connections, reading each one on its own thread,
+///until the visualizer is gone
+fn accept<S: Read + Send + 'static>(incoming: impl Iterator<Item = io::Result<S>>,
+                                    tx: Sender<VisData>, status: Sender<LinkStatus>) {
+    let closed = Arc::new(AtomicBool::new(false));
+    for stream in incoming {
+        if closed.load(Ordering::Relaxed) {
+            break;
+        }
+        match stream {
+            Ok(s) => {
+                let (tx, status, closed) = (tx.clone(), status.clone(), Arc::clone(&closed));
+                thread::spawn(move || {
+                    if !forward(s, &tx, &status) {
+                        closed.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+            Err(e) => { let _ = status.send(LinkStatus::AcceptFailed(e.to_string())); }
+        }
+    }
+}
+
+///reads updates from a robot connection until it closes,
+///returns false if the visualizer is gone and listening should stop
+fn forward(stream: impl Read, tx: &Sender<VisData>, status: &Sender<LinkStatus>) -> bool {
+    let _ = status.send(LinkStatus::Connected);
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match WireData::from_line(&line) {
+            Ok(data) => {
+                if tx.send(VisData::from(data)).is_err() {
+                    return false;
+                }
+            }
+            Err(e) => { let _ = status.send(LinkStatus::Malformed(e.to_string())); }
+        }
+    }
+    let _ = status.send(LinkStatus::Disconnected);
+    true
+}
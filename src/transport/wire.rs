@@ -0,0 +1,279 @@
+use std::ops::Range;
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use serde::{Deserialize, Serialize};
+
+use crate::visualizer::VisData;
+use crate::visualizer::visstate::{Severity, VisAction};
+
+///serializable form of [VisData], sent as one JSON object per line
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WireData {
+    pub energy: usize,
+    pub coordinates: (usize, usize),
+    #[serde(default)]
+    pub discovered_tiles: Option<Vec<Vec<Option<WireTile>>>>,
+    #[serde(default)]
+    pub backpack: Option<Vec<(WireContent, usize)>>,
+    #[serde(default)]
+    pub weather: Option<WireWeather>,
+    #[serde(default)]
+    pub rizz_text: Option<String>,
+    #[serde(default)]
+    pub robot_id: usize,
+    #[serde(default)]
+    pub robot_name: Option<String>,
+    #[serde(default)]
+    pub backpack_size: Option<usize>,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub time_of_day: Option<(u8, u8)>,
+    #[serde(default)]
+    pub forecast: Option<Vec<(u8, WireWeather)>>,
+    #[serde(default)]
+    pub weather_intensity: Option<f32>,
+    #[serde(default)]
+    pub action: Option<(VisAction, (usize, usize))>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WireTile {
+    pub tile_type: WireTileType,
+    pub content: WireContent,
+    pub elevation: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WireTileType {
+    DeepWater,
+    ShallowWater,
+    Sand,
+    Grass,
+    Street,
+    Hill,
+    Mountain,
+    Snow,
+    Lava,
+    Teleport(bool),
+    Wall,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WireContent {
+    Rock(usize),
+    Tree(usize),
+    Garbage(usize),
+    Fire,
+    Coin(usize),
+    Bin(Range<usize>),
+    Crate(Range<usize>),
+    Bank(Range<usize>),
+    Water(usize),
+    Market(usize),
+    Fish(usize),
+    Building,
+    Bush(usize),
+    JollyBlock(usize),
+    Scarecrow,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WireWeather {
+    Sunny,
+    Rainy,
+    Foggy,
+    TropicalMonsoon,
+    TrentinoSnow,
+}
+
+impl WireData {
+    pub fn to_line(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+    pub fn from_line(line: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(line)
+    }
+}
+
+impl From<&VisData> for WireData {
+    fn from(data: &VisData) -> Self {
+        Self {
+            energy: data.recv_energy,
+            coordinates: data.recv_coordinates,
+            discovered_tiles: data.recv_discovered_tiles.as_ref().map(|view| {
+                view.iter()
+                    .map(|row| row.iter().map(|t| t.as_ref().map(WireTile::from)).collect())
+                    .collect()
+            }),
+            backpack: data.recv_backpack.as_ref().map(|b| {
+                b.iter().map(|(c, q)| (WireContent::from(c), *q)).collect()
+            }),
+            weather: data.recv_weather.map(WireWeather::from),
+            rizz_text: data.recv_rizz_text.clone(),
+            robot_id: data.recv_robot_id,
+            robot_name: data.recv_robot_name.clone(),
+            backpack_size: data.recv_backpack_size,
+            severity: data.recv_severity,
+            time_of_day: data.recv_time_of_day,
+            forecast: data.recv_forecast.as_ref().map(|f| {
+                f.iter().map(|(h, w)| (*h, WireWeather::from(*w))).collect()
+            }),
+            weather_intensity: data.recv_weather_intensity,
+            action: data.recv_action,
+        }
+    }
+}
+
+impl From<WireData> for VisData {
+    fn from(data: WireData) -> Self {
+        let mut vis_data = VisData::new(
+            data.energy,
+            data.coordinates,
+            data.discovered_tiles.map(|view| {
+                view.into_iter()
+                    .map(|row| row.into_iter().map(|t| t.map(Tile::from)).collect())
+                    .collect()
+            }),
+            data.backpack.map(|b| b.into_iter().map(|(c, q)| (Content::from(c), q)).collect()),
+            data.weather.map(WeatherType::from),
+            data.rizz_text,
+        ).with_robot(data.robot_id);
+        vis_data.recv_robot_name = data.robot_name;
+        vis_data.recv_backpack_size = data.backpack_size;
+        vis_data.recv_severity = data.severity;
+        vis_data.recv_time_of_day = data.time_of_day;
+        vis_data.recv_weather_intensity = data.weather_intensity;
+        vis_data.recv_action = data.action;
+        vis_data.recv_forecast = data.forecast.map(|f| f.into_iter().map(|(h, w)| (h, WeatherType::from(w))).collect());
+        vis_data
+    }
+}
+
+impl From<&Tile> for WireTile {
+    fn from(tile: &Tile) -> Self {
+        Self {
+            tile_type: WireTileType::from(tile.tile_type),
+            content: WireContent::from(&tile.content),
+            elevation: tile.elevation,
+        }
+    }
+}
+
+impl From<WireTile> for Tile {
+    fn from(tile: WireTile) -> Self {
+        Tile {
+            tile_type: TileType::from(tile.tile_type),
+            content: Content::from(tile.content),
+            elevation: tile.elevation,
+        }
+    }
+}
+
+impl From<TileType> for WireTileType {
+    fn from(t: TileType) -> Self {
+        match t {
+            TileType::DeepWater => WireTileType::DeepWater,
+            TileType::ShallowWater => WireTileType::ShallowWater,
+            TileType::Sand => WireTileType::Sand,
+            TileType::Grass => WireTileType::Grass,
+            TileType::Street => WireTileType::Street,
+            TileType::Hill => WireTileType::Hill,
+            TileType::Mountain => WireTileType::Mountain,
+            TileType::Snow => WireTileType::Snow,
+            TileType::Lava => WireTileType::Lava,
+            TileType::Teleport(b) => WireTileType::Teleport(b),
+            TileType::Wall => WireTileType::Wall,
+        }
+    }
+}
+
+impl From<WireTileType> for TileType {
+    fn from(t: WireTileType) -> Self {
+        match t {
+            WireTileType::DeepWater => TileType::DeepWater,
+            WireTileType::ShallowWater => TileType::ShallowWater,
+            WireTileType::Sand => TileType::Sand,
+            WireTileType::Grass => TileType::Grass,
+            WireTileType::Street => TileType::Street,
+            WireTileType::Hill => TileType::Hill,
+            WireTileType::Mountain => TileType::Mountain,
+            WireTileType::Snow => TileType::Snow,
+            WireTileType::Lava => TileType::Lava,
+            WireTileType::Teleport(b) => TileType::Teleport(b),
+            WireTileType::Wall => TileType::Wall,
+        }
+    }
+}
+
+impl From<&Content> for WireContent {
+    fn from(c: &Content) -> Self {
+        match c {
+            Content::Rock(v) => WireContent::Rock(*v),
+            Content::Tree(v) => WireContent::Tree(*v),
+            Content::Garbage(v) => WireContent::Garbage(*v),
+            Content::Fire => WireContent::Fire,
+            Content::Coin(v) => WireContent::Coin(*v),
+            Content::Bin(r) => WireContent::Bin(r.clone()),
+            Content::Crate(r) => WireContent::Crate(r.clone()),
+            Content::Bank(r) => WireContent::Bank(r.clone()),
+            Content::Water(v) => WireContent::Water(*v),
+            Content::Market(v) => WireContent::Market(*v),
+            Content::Fish(v) => WireContent::Fish(*v),
+            Content::Building => WireContent::Building,
+            Content::Bush(v) => WireContent::Bush(*v),
+            Content::JollyBlock(v) => WireContent::JollyBlock(*v),
+            Content::Scarecrow => WireContent::Scarecrow,
+            _ => WireContent::None,
+        }
+    }
+}
+
+impl From<WireContent> for Content {
+    fn from(c: WireContent) -> Self {
+        match c {
+            WireContent::Rock(v) => Content::Rock(v),
+            WireContent::Tree(v) => Content::Tree(v),
+            WireContent::Garbage(v) => Content::Garbage(v),
+            WireContent::Fire => Content::Fire,
+            WireContent::Coin(v) => Content::Coin(v),
+            WireContent::Bin(r) => Content::Bin(r),
+            WireContent::Crate(r) => Content::Crate(r),
+            WireContent::Bank(r) => Content::Bank(r),
+            WireContent::Water(v) => Content::Water(v),
+            WireContent::Market(v) => Content::Market(v),
+            WireContent::Fish(v) => Content::Fish(v),
+            WireContent::Building => Content::Building,
+            WireContent::Bush(v) => Content::Bush(v),
+            WireContent::JollyBlock(v) => Content::JollyBlock(v),
+            WireContent::Scarecrow => Content::Scarecrow,
+            WireContent::None => Content::None,
+        }
+    }
+}
+
+impl From<WeatherType> for WireWeather {
+    fn from(w: WeatherType) -> Self {
+        match w {
+            WeatherType::Sunny => WireWeather::Sunny,
+            WeatherType::Rainy => WireWeather::Rainy,
+            WeatherType::Foggy => WireWeather::Foggy,
+            WeatherType::TropicalMonsoon => WireWeather::TropicalMonsoon,
+            WeatherType::TrentinoSnow => WireWeather::TrentinoSnow,
+        }
+    }
+}
+
+impl From<WireWeather> for WeatherType {
+    fn from(w: WireWeather) -> Self {
+        match w {
+            WireWeather::Sunny => WeatherType::Sunny,
+            WireWeather::Rainy => WeatherType::Rainy,
+            WireWeather::Foggy => WeatherType::Foggy,
+            WireWeather::TropicalMonsoon => WeatherType::TropicalMonsoon,
+            WireWeather::TrentinoSnow => WeatherType::TrentinoSnow,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::ops::Range;
+
+///side of a chunk, in tiles
+pub const CHUNK_SIZE: usize = 32;
+
+///[size x size] grid of optional values, stored in [CHUNK_SIZE] square chunks
+///allocated the first time one of their cells is written
+///
+///a huge world where only a small region is discovered costs just the chunks
+///around that region, and iterating skips the empty ones
+#[derive(Clone, Debug)]
+pub struct ChunkMap<T> {
+    size: usize,
+    ///chunks per side
+    side: usize,
+    ///chunks row by row, None until something is written in them
+    chunks: Vec<Option<Box<[Option<T>]>>>,
+}
+
+impl<T> ChunkMap<T> {
+    ///creates an empty [size x size] grid, no chunk is allocated yet
+    pub fn new(size: usize) -> Self {
+        let side = size.div_ceil(CHUNK_SIZE);
+        Self {
+            size,
+            side,
+            chunks: (0..side * side).map(|_| None).collect(),
+        }
+    }
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.size || col >= self.size {
+            return None;
+        }
+        self.chunks[self.chunk_index(row, col)].as_ref()?[cell_index(row, col)].as_ref()
+    }
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row >= self.size || col >= self.size {
+            return None;
+        }
+        let chunk = self.chunk_index(row, col);
+        self.chunks[chunk].as_mut()?[cell_index(row, col)].as_mut()
+    }
+    ///writes 'value' at [row][col], allocating its chunk if needed, and returns the previous value
+    ///
+    ///coordinates outside the grid are ignored
+    pub fn insert(&mut self, row: usize, col: usize, value: T) -> Option<T> {
+        if row >= self.size || col >= self.size {
+            return None;
+        }
+        let chunk = self.chunk_index(row, col);
+        let cells = self.chunks[chunk].get_or_insert_with(|| (0..CHUNK_SIZE * CHUNK_SIZE).map(|_| None).collect());
+        cells[cell_index(row, col)].replace(value)
+    }
+    ///rows and columns covered by each allocated chunk, row by row
+    pub fn chunks(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+        self.chunks.iter().enumerate()
+            .filter(|(_, chunk)| chunk.is_some())
+            .map(move |(i, _)| {
+                let (row, col) = (i / self.side * CHUNK_SIZE, i % self.side * CHUNK_SIZE);
+                (row..(row + CHUNK_SIZE).min(self.size), col..(col + CHUNK_SIZE).min(self.size))
+            })
+    }
+    ///iterates over the values with their coordinates, chunk by chunk
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.iter_in(0..self.size, 0..self.size)
+    }
+    ///iterates over the values in 'rows' and 'cols', chunk by chunk,
+    ///looking only at the chunks overlapping them
+    pub fn iter_in(&self, rows: Range<usize>, cols: Range<usize>) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let rows = clamp(rows, self.size);
+        let cols = clamp(cols, self.size);
+        let chunk_rows = rows.start / CHUNK_SIZE..rows.end.div_ceil(CHUNK_SIZE);
+        let chunk_cols = cols.start / CHUNK_SIZE..cols.end.div_ceil(CHUNK_SIZE);
+        chunk_rows
+            .flat_map(move |chunk_row| chunk_cols.clone().map(move |chunk_col| (chunk_row, chunk_col)))
+            .filter_map(move |(chunk_row, chunk_col)| {
+                self.chunks[chunk_row * self.side + chunk_col].as_deref()
+                    .map(|cells| (chunk_row * CHUNK_SIZE, chunk_col * CHUNK_SIZE, cells))
+            })
+            .flat_map(move |(first_row, first_col, cells)| {
+                let cols = cols.start.max(first_col)..cols.end.min(first_col + CHUNK_SIZE);
+                (rows.start.max(first_row)..rows.end.min(first_row + CHUNK_SIZE)).flat_map(move |row| {
+                    cols.clone().filter_map(move |col| cells[cell_index(row, col)].as_ref().map(|v| ((row, col), v)))
+                })
+            })
+    }
+    fn chunk_index(&self, row: usize, col: usize) -> usize {
+        row / CHUNK_SIZE * self.side + col / CHUNK_SIZE
+    }
+}
+
+///position of [row][col] inside its chunk
+fn cell_index(row: usize, col: usize) -> usize {
+    row % CHUNK_SIZE * CHUNK_SIZE + col % CHUNK_SIZE
+}
+
+///'range' cut to the grid, never reversed
+fn clamp(range: Range<usize>, size: usize) -> Range<usize> {
+    let end = range.end.min(size);
+    range.start.min(end)..end
+}
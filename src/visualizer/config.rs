@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::visualizer::visenergy::MAX_ENERGY;
+use crate::visualizer::vismap::{LOD_IMAGE_BELOW, LOD_SIMPLE_BELOW, WEATHER_TRANSITION};
+use crate::visualizer::vismotion::ROBOT_MOVE_DURATION;
+use crate::visualizer::visnotifications::NotificationKind;
+
+///options of the window visualizer, see [crate::start_with_config]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisConfig {
+    ///show the rizz text in a bubble above the robot
+    pub speech_bubbles: bool,
+    ///how long a speech bubble stays on screen
+    pub speech_duration: Duration,
+    ///notifications shown as toasts and written to the console
+    pub notifications: HashSet<NotificationKind>,
+    ///energy under which [NotificationKind::LowEnergy] is raised
+    pub low_energy_threshold: usize,
+    ///energy of a full energy bar
+    pub max_energy: usize,
+    ///steps of the world on the same tile after which [NotificationKind::Stuck] is raised,
+    ///a step is an update advancing the time of day, or any update when the time is not sent
+    pub stuck_steps: usize,
+    ///seed of the weather effects, random when None
+    pub weather_seed: Option<u64>,
+    ///how long the cross-fade between two weather types lasts
+    pub weather_transition: Duration,
+    ///zoom under which tiles are drawn as flat colors
+    pub lod_simple_below: f32,
+    ///zoom under which the map is drawn as a single image
+    pub lod_image_below: f32,
+    ///how long robots take to walk to the next tile
+    pub robot_move_duration: Duration,
+    ///whether destroying, collecting, putting and extinguishing show an effect on the map
+    pub action_effects: bool,
+}
+
+impl Default for VisConfig {
+    fn default() -> Self {
+        Self {
+            speech_bubbles: true,
+            speech_duration: Duration::from_secs(4),
+            notifications: NotificationKind::ALL.into_iter().collect(),
+            low_energy_threshold: 200,
+            max_energy: MAX_ENERGY,
+            stuck_steps: 10,
+            weather_seed: None,
+            weather_transition: WEATHER_TRANSITION,
+            lod_simple_below: LOD_SIMPLE_BELOW,
+            lod_image_below: LOD_IMAGE_BELOW,
+            robot_move_duration: ROBOT_MOVE_DURATION,
+            action_effects: true,
+        }
+    }
+}
+
+impl VisConfig {
+    pub fn with_speech_bubbles(mut self, show: bool) -> Self {
+        self.speech_bubbles = show;
+        self
+    }
+    pub fn with_speech_duration(mut self, duration: Duration) -> Self {
+        self.speech_duration = duration;
+        self
+    }
+    ///turns the notifications of 'kind' on or off
+    pub fn with_notification(mut self, kind: NotificationKind, enabled: bool) -> Self {
+        if enabled {
+            self.notifications.insert(kind);
+        } else {
+            self.notifications.remove(&kind);
+        }
+        self
+    }
+    pub fn with_low_energy_threshold(mut self, threshold: usize) -> Self {
+        self.low_energy_threshold = threshold;
+        self
+    }
+    ///sets the energy of a full energy bar, robotics_lib's maximum by default
+    pub fn with_max_energy(mut self, max_energy: usize) -> Self {
+        self.max_energy = max_energy;
+        self
+    }
+    ///makes the weather effects reproducible, runs with the same seed render the same particles
+    pub fn with_weather_seed(mut self, seed: u64) -> Self {
+        self.weather_seed = Some(seed);
+        self
+    }
+    ///sets how long the weather takes to cross-fade, zero switches instantly
+    pub fn with_weather_transition(mut self, duration: Duration) -> Self {
+        self.weather_transition = duration;
+        self
+    }
+    ///sets the zooms under which the map loses detail, see [crate::visualizer::vismap::Lod]
+    pub fn with_lod_scales(mut self, simple_below: f32, image_below: f32) -> Self {
+        self.lod_simple_below = simple_below;
+        self.lod_image_below = image_below;
+        self
+    }
+    pub fn with_stuck_steps(mut self, steps: usize) -> Self {
+        self.stuck_steps = steps;
+        self
+    }
+    ///sets how long robots take to walk to the next tile, zero jumps straight to it
+    pub fn with_robot_move_duration(mut self, duration: Duration) -> Self {
+        self.robot_move_duration = duration;
+        self
+    }
+    pub fn with_action_effects(mut self, show: bool) -> Self {
+        self.action_effects = show;
+        self
+    }
+}
@@ -0,0 +1,117 @@
+use robotics_lib::world::tile::{Content, TileType};
+
+///plain color, each channel from 0 to 1, shared by the window and the front ends without tetra
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb::new(1.0, 1.0, 1.0);
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b }
+    }
+    ///channels from 0 to 255, for the terminal and the browser
+    pub fn bytes(self) -> [u8; 3] {
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [channel(self.r), channel(self.g), channel(self.b)]
+    }
+}
+
+///robot tints before they repeat, see [robot_tint]
+pub const ROBOT_TINTS: usize = 6;
+
+///fixed position of a content kind, used to sort backpack slots and legends
+pub fn content_order(content: &Content) -> usize {
+    match content {
+        Content::Rock(_) => 0,
+        Content::Tree(_) => 1,
+        Content::Garbage(_) => 2,
+        Content::Fire => 3,
+        Content::Coin(_) => 4,
+        Content::Bin(_) => 5,
+        Content::Crate(_) => 6,
+        Content::Bank(_) => 7,
+        Content::Water(_) => 8,
+        Content::Market(_) => 9,
+        Content::Fish(_) => 10,
+        Content::Building => 11,
+        Content::Bush(_) => 12,
+        Content::JollyBlock(_) => 13,
+        Content::Scarecrow => 14,
+        _ => 15,
+    }
+}
+///human readable name of a content kind
+pub fn content_name(content: &Content) -> &'static str {
+    match content {
+        Content::Rock(_) => "Rock",
+        Content::Tree(_) => "Tree",
+        Content::Garbage(_) => "Garbage",
+        Content::Fire => "Fire",
+        Content::Coin(_) => "Coin",
+        Content::Bin(_) => "Bin",
+        Content::Crate(_) => "Crate",
+        Content::Bank(_) => "Bank",
+        Content::Water(_) => "Water",
+        Content::Market(_) => "Market",
+        Content::Fish(_) => "Fish",
+        Content::Building => "Building",
+        Content::Bush(_) => "Bush",
+        Content::JollyBlock(_) => "JollyBlock",
+        Content::Scarecrow => "Scarecrow",
+        _ => "None",
+    }
+}
+///tint of the robot with the given id, also used for its discoveries
+///
+///the first robot keeps the sprite's own colors
+pub fn robot_tint(id: usize) -> Rgb {
+    match id % ROBOT_TINTS {
+        0 => Rgb::WHITE,
+        1 => Rgb::new(1.0, 0.45, 0.45),
+        2 => Rgb::new(0.45, 0.65, 1.0),
+        3 => Rgb::new(0.5, 1.0, 0.5),
+        4 => Rgb::new(1.0, 0.85, 0.35),
+        _ => Rgb::new(0.85, 0.5, 1.0),
+    }
+}
+///flat color of a tile type, used when the map is too small for the tileset
+pub fn tile_color(tile_type: TileType) -> Rgb {
+    match tile_type {
+        TileType::DeepWater => Rgb::new(0.1, 0.2, 0.55),
+        TileType::ShallowWater => Rgb::new(0.25, 0.5, 0.85),
+        TileType::Sand => Rgb::new(0.9, 0.82, 0.55),
+        TileType::Grass => Rgb::new(0.35, 0.7, 0.3),
+        TileType::Street => Rgb::new(0.45, 0.45, 0.45),
+        TileType::Hill => Rgb::new(0.5, 0.6, 0.3),
+        TileType::Mountain => Rgb::new(0.5, 0.42, 0.35),
+        TileType::Snow => Rgb::new(0.95, 0.95, 1.0),
+        TileType::Lava => Rgb::new(0.9, 0.3, 0.05),
+        TileType::Teleport(_) => Rgb::new(0.7, 0.3, 0.9),
+        TileType::Wall => Rgb::new(0.25, 0.2, 0.2),
+    }
+}
+///color of a content kind in charts and legends
+pub fn content_color(content: &Content) -> Rgb {
+    match content {
+        Content::Rock(_) => Rgb::new(0.6, 0.6, 0.6),
+        Content::Tree(_) => Rgb::new(0.2, 0.7, 0.2),
+        Content::Garbage(_) => Rgb::new(0.55, 0.45, 0.25),
+        Content::Fire => Rgb::new(1.0, 0.4, 0.1),
+        Content::Coin(_) => Rgb::new(1.0, 0.85, 0.2),
+        Content::Bin(_) => Rgb::new(0.4, 0.5, 0.4),
+        Content::Crate(_) => Rgb::new(0.75, 0.55, 0.3),
+        Content::Bank(_) => Rgb::new(0.9, 0.9, 0.5),
+        Content::Water(_) => Rgb::new(0.3, 0.55, 1.0),
+        Content::Market(_) => Rgb::new(0.9, 0.4, 0.7),
+        Content::Fish(_) => Rgb::new(0.4, 0.85, 0.9),
+        Content::Building => Rgb::new(0.7, 0.3, 0.3),
+        Content::Bush(_) => Rgb::new(0.5, 0.85, 0.3),
+        Content::JollyBlock(_) => Rgb::new(0.8, 0.5, 1.0),
+        Content::Scarecrow => Rgb::new(0.85, 0.75, 0.55),
+        _ => Rgb::WHITE,
+    }
+}
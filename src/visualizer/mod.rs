@@ -1,32 +1,78 @@
 use std::collections::HashMap;
-use std::sync::mpsc::Receiver;
 
 use robotics_lib::world::environmental_conditions::WeatherType;
 use robotics_lib::world::tile::{Content, Tile};
-use robotics_lib::world::world_generator::Generator;
-use tetra::{Context, graphics, input, State, TetraError};
-use tetra::graphics::Color;
-use tetra::input::Key;
 
-use crate::visualizer::textures::Texturizable;
-use crate::visualizer::visbackpack::VisBackPack;
-use crate::visualizer::visenergy::VisEnergy;
-use crate::visualizer::vismap::VisMap;
-use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::visualizer::visstate::{Severity, VisAction};
 
+#[cfg(feature = "tetra")]
+pub use crate::visualizer::window::Visualizer;
+
+#[cfg(feature = "tetra")]
+pub mod config;
+pub mod chunkmap;
+pub mod contents;
+#[cfg(feature = "tetra")]
 pub mod textures;
+#[cfg(feature = "tetra")]
+pub mod particles;
+#[cfg(feature = "tetra")]
+pub mod renderer;
+#[cfg(feature = "tetra")]
+pub mod tetrarenderer;
+#[cfg(feature = "tetra")]
+pub mod recorder;
+pub mod visstate;
+#[cfg(feature = "tetra")]
+pub mod viscamera;
+#[cfg(feature = "tetra")]
+pub mod visconsole;
+#[cfg(feature = "tetra")]
 pub mod vismap;
+#[cfg(feature = "tetra")]
+pub mod vismotion;
+#[cfg(feature = "tetra")]
+pub mod viseffects;
+#[cfg(feature = "tetra")]
 pub mod visenergy;
+#[cfg(feature = "tetra")]
 pub mod visbackpack;
+#[cfg(feature = "tetra")]
+pub mod visbackpackchart;
+#[cfg(feature = "tetra")]
+pub mod visinventory;
+#[cfg(feature = "tetra")]
 pub mod visweather;
+#[cfg(feature = "tetra")]
+pub mod visforecast;
+#[cfg(feature = "tetra")]
+pub mod vislighting;
+#[cfg(feature = "tetra")]
+pub mod visspeech;
+#[cfg(feature = "tetra")]
+pub mod visnotifications;
+#[cfg(feature = "terminal")]
+pub mod visterminal;
+#[cfg(feature = "tetra")]
+mod window;
 
+#[cfg(feature = "tetra")]
 pub(crate) const PIXEL: f32 = 64.0;
+#[cfg(feature = "tetra")]
 pub(crate) const SCALE: f32 = 0.4;
+#[cfg(feature = "tetra")]
 pub(crate) const BP_SCALE: f32 = 0.5;
+#[cfg(feature = "tetra")]
 pub(crate) const TOP_OFFSET: f32 = 64.0;
+///left edge of the energy, clock and forecast column
+#[cfg(feature = "tetra")]
+pub(crate) const HUD_X: f32 = crate::WINDOW_WIDTH as f32 / 1.25 - PIXEL * SCALE;
+///robot id of updates that don't set one
+pub const DEFAULT_ROBOT: usize = 0;
 
 
 ///struct containing data to be transmitted between threads
+#[derive(Clone)]
 pub struct VisData {
     pub recv_energy: usize,
     pub recv_coordinates: (usize, usize),
@@ -34,6 +80,21 @@ pub struct VisData {
     pub recv_backpack: Option<HashMap<Content, usize>>,
     pub recv_weather: Option<WeatherType>,
     pub recv_rizz_text: Option<String>,
+    ///robot the update comes from, see [VisData::with_robot]
+    pub recv_robot_id: usize,
+    pub recv_robot_name: Option<String>,
+    ///capacity of the robot's backpack, see [VisData::with_backpack_size]
+    pub recv_backpack_size: Option<usize>,
+    ///importance of the rizz text, see [VisData::with_severity]
+    pub recv_severity: Severity,
+    ///hour and minute of the world, see [VisData::with_time_of_day]
+    pub recv_time_of_day: Option<(u8, u8)>,
+    ///upcoming weather, see [VisData::with_forecast]
+    pub recv_forecast: Option<Vec<(u8, WeatherType)>>,
+    ///strength of the weather, see [VisData::with_weather_intensity]
+    pub recv_weather_intensity: Option<f32>,
+    ///what the robot did on a tile, see [VisData::with_action]
+    pub recv_action: Option<(VisAction, (usize, usize))>,
 }
 
 impl VisData {
@@ -73,6 +134,14 @@ impl VisData {
                     None
                 }
             },
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
 
@@ -90,6 +159,14 @@ impl VisData {
             recv_backpack: None,
             recv_weather: None,
             recv_rizz_text: None,
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
     /// - energy : {    }
@@ -106,6 +183,14 @@ impl VisData {
             recv_backpack: None,
             recv_weather: None,
             recv_rizz_text: None,
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
     /// - energy : {    }
@@ -122,6 +207,14 @@ impl VisData {
             recv_backpack: Some(backpack),
             recv_weather: None,
             recv_rizz_text: None,
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
     /// - energy : {    }
@@ -138,6 +231,14 @@ impl VisData {
             recv_backpack: None,
             recv_weather: Some(weather_type),
             recv_rizz_text: None,
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
     /// - energy : {    }
@@ -154,144 +255,56 @@ impl VisData {
             recv_backpack: None,
             recv_weather: None,
             recv_rizz_text: Some(rizz_text),
+            recv_robot_id: DEFAULT_ROBOT,
+            recv_robot_name: None,
+            recv_backpack_size: None,
+            recv_severity: Severity::Info,
+            recv_time_of_day: None,
+            recv_forecast: None,
+            recv_weather_intensity: None,
+            recv_action: None,
         }
     }
-}
-
-pub struct Visualizer {
-    style: usize,
-
-    map: VisMap,
-    texts: VisEnergy,
-    backpack: VisBackPack,
-
-    receiver: Receiver<VisData>,
-    show_backpack: bool,
-    map_pos: (f32, f32),
-    scale: f32,
-    first: bool,
-}
-
-impl Visualizer {
-    pub fn new(ctx: &mut Context, size: usize, receiver: Receiver<VisData>) -> tetra::Result<Visualizer> {
-        Ok(
-            Self {
-                style: 0,
-                map: VisMap::new(size),
-                texts: VisEnergy::new(ctx),
-                backpack: VisBackPack::new(ctx, 16),
-                receiver,
-                show_backpack: true,
-                map_pos: (0.0, 0.0),
-                scale: SCALE,
-                first : true,
-            }
-        )
+    ///tags the update with the robot sending it, when more robots share the world
+    pub fn with_robot(mut self, id: usize) -> Self {
+        self.recv_robot_id = id;
+        self
     }
-    pub(crate) fn update_map(&mut self, new_discovered: Vec<Vec<Option<Tile>>>, ctx: &mut Context) {
-        self.map.update_map(new_discovered)
+    ///sets the name shown next to the robot, it is remembered for later updates
+    pub fn with_robot_name(mut self, name: impl Into<String>) -> Self {
+        self.recv_robot_name = Some(name.into());
+        self
     }
-    pub(crate) fn update_robot_pos(&mut self, new_pos: (usize, usize)) {
-        self.map.update_robot_pos(new_pos)
+    ///sets how important the rizz text is, messages are [Severity::Info] otherwise
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.recv_severity = severity;
+        self
     }
-    pub(crate) fn update_energy(&mut self, energy: usize) {
-        self.texts.update_energy(energy)
+    ///sets the time of day (robotics_lib's `EnvironmentalConditions::get_time_of_day`),
+    ///used for the clock and the lighting of the map
+    pub fn with_time_of_day(mut self, hour: u8, minute: u8) -> Self {
+        self.recv_time_of_day = Some((hour, minute));
+        self
     }
-    pub(crate) fn update_backpack(&mut self, backpack: HashMap<Content, usize>) {
-        self.backpack.update(backpack);
+    ///sets the upcoming weather as (starting hour, weather) pairs, in chronological order
+    pub fn with_forecast(mut self, forecast: Vec<(u8, WeatherType)>) -> Self {
+        self.recv_forecast = Some(forecast);
+        self
     }
-    pub(crate) fn update_weather(&mut self, weather_type: WeatherType) {
-        self.map.update_weather(weather_type);
+    ///sets how strong the weather is, from 0 (barely there) to 1, it is remembered for later updates
+    pub fn with_weather_intensity(mut self, intensity: f32) -> Self {
+        self.recv_weather_intensity = Some(intensity);
+        self
     }
-    pub(crate) fn update_rizz(&mut self, rizz: String) {
-        self.texts.update_rizz(rizz);
+    ///tells that the robot did 'action' on 'tile', to show its effect even when
+    ///it can't be told from the content change of the tile
+    pub fn with_action(mut self, action: VisAction, tile: (usize, usize)) -> Self {
+        self.recv_action = Some((action, tile));
+        self
     }
-}
-
-impl State for Visualizer {
-    fn update(&mut self, ctx: &mut Context) -> Result<(), TetraError> {
-        //received data handling
-        let data_res = self.receiver.try_recv();
-        match data_res {
-            Ok(data) => {
-                self.update_energy(data.recv_energy);
-                //println!("received {}", data.recv_energy);
-                let c = data.recv_coordinates;
-                self.update_robot_pos(c);
-
-                if self.first {
-                    self.map_pos = (-(PIXEL * self.scale * c.0 as f32)/2.0, -(PIXEL * self.scale * c.1 as f32)/2.0);
-                }
-                self.first = false;
-
-                if let Some(view) = data.recv_discovered_tiles {
-                    self.update_map(view, ctx)
-                }
-                if let Some(backp) = data.recv_backpack {
-                    self.update_backpack(backp)
-                }
-                if let Some(w) = data.recv_weather {
-                    self.update_weather(w)
-                }
-                if let Some(s) = data.recv_rizz_text {
-                    self.update_rizz(s)
-                }
-            }
-            Err(e) => {
-                //println!("{e}")
-            }
-        }
-
-        //key input handling
-        let scale = self.scale;
-        if input::get_keys_pressed(ctx).next().is_some() {
-            match input::get_keys_pressed(ctx).next().unwrap() {
-                //map movement
-                Key::A | Key::Left => { self.map_pos.0 += PIXEL * scale; }
-                Key::D | Key::Right => { self.map_pos.0 -= PIXEL * scale; }
-                Key::W | Key::Up => { self.map_pos.1 += PIXEL * scale; }
-                Key::S | Key::Down => { self.map_pos.1 -= PIXEL * scale; }
-                //backpack showing
-                Key::X | Key::Space => { self.show_backpack = !self.show_backpack; }
-                //zoom
-                Key::I => {
-                    self.scale += 0.1;
-                    self.map_pos.1 -= PIXEL * scale;
-                    self.map_pos.0 -= PIXEL * scale;
-                }
-                Key::O => {
-                    self.scale -= 0.05;
-                    self.map_pos.1 -= PIXEL * scale;
-                    self.map_pos.0 -= PIXEL * scale;
-                }
-                //style
-                Key::Num0 => { self.style = 0 }
-                Key::Num1 => { self.style = 1 }
-                Key::Num2 => { self.style = 2 }
-                Key::Num3 => { self.style = 3 }
-                _ => {}
-            }
-            if input::get_keys_down(ctx).next().is_some() {
-                match input::get_keys_down(ctx).next().unwrap() {
-                    //map movement
-                    Key::A | Key::Left => { self.map_pos.0 += PIXEL * scale / 4.0; }
-                    Key::D | Key::Right => { self.map_pos.0 -= PIXEL * scale / 4.0; }
-                    Key::W | Key::Up => { self.map_pos.1 += PIXEL * scale / 4.0; }
-                    Key::S | Key::Down => { self.map_pos.1 -= PIXEL * scale / 4.0; }
-                    _ => {}
-                }
-            }
-        }
-        Ok(())
-    }
-
-    fn draw(&mut self, ctx: &mut Context) -> Result<(), TetraError> {
-        graphics::clear(ctx, Color::rgb(0.0, 0.0, 0.0));
-        self.map.new_draw(ctx, self.map_pos, self.scale, self.style);
-        self.texts.draw(ctx);
-        if self.show_backpack {
-            self.backpack.draw(ctx, (0.0, 0.0), self.style)
-        }
-        Ok(())
+    ///sets the backpack capacity (robotics_lib's `BackPack::get_size`), it is remembered for later updates
+    pub fn with_backpack_size(mut self, size: usize) -> Self {
+        self.recv_backpack_size = Some(size);
+        self
     }
 }
@@ -0,0 +1,169 @@
+use rand::Rng;
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::SCALE;
+use crate::visualizer::renderer::{Renderer, Sprite};
+use crate::visualizer::viscamera::Camera;
+
+///coordinates the particles of an emitter live in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Space {
+    ///window pixels, the particles stay still when the camera moves
+    Screen,
+    ///map pixels at zoom 1, the particles scroll and zoom with the map
+    Map,
+}
+
+///where new particles appear inside the spawn area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spawn {
+    ///along the top edge, for anything falling
+    Top,
+    ///anywhere in the area
+    Anywhere,
+}
+
+///description of how an emitter creates its particles
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    pub sprite: Sprite,
+    pub space: Space,
+    pub spawn: Spawn,
+    ///particles created per second
+    pub rate: f32,
+    ///particles alive at most
+    pub max: usize,
+    ///pixels per second
+    pub velocity: Vec2<f32>,
+    ///random variation added to the velocity, in both directions
+    pub jitter: Vec2<f32>,
+    ///seconds a particle lives
+    pub lifetime: f32,
+    pub scale: Vec2<f32>,
+    pub color: Color,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub position: Vec2<f32>,
+    pub velocity: Vec2<f32>,
+    ///seconds since the particle was created
+    pub age: f32,
+}
+
+///particles created by an [Emitter]
+#[derive(Clone, Debug)]
+pub struct ParticleSystem {
+    emitter: Emitter,
+    particles: Vec<Particle>,
+    ///fraction of particle not spawned yet
+    pending: f32,
+    ///multiplies the spawn rate of the emitter, 0 stops spawning
+    rate_factor: f32,
+}
+
+impl ParticleSystem {
+    pub fn new(emitter: Emitter) -> Self {
+        Self {
+            emitter,
+            particles: Vec::new(),
+            pending: 0.0,
+            rate_factor: 1.0,
+        }
+    }
+    ///scales the spawn rate, to make the emitter stronger, weaker or drain it with 0
+    pub fn set_rate_factor(&mut self, factor: f32) {
+        self.rate_factor = factor.max(0.0);
+    }
+    pub fn emitter(&self) -> &Emitter {
+        &self.emitter
+    }
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+    ///fills 'area' as if the emitter had been running for a whole particle life
+    pub fn prewarm(&mut self, rng: &mut impl Rng, area: Rectangle) {
+        let count = ((self.emitter.rate * self.rate_factor * self.emitter.lifetime) as usize).min(self.emitter.max);
+        for _ in 0..count {
+            let mut particle = self.spawn_particle(rng, area);
+            particle.age = rng.gen::<f32>() * self.emitter.lifetime;
+            particle.position = Vec2::new(
+                area.x + rng.gen::<f32>() * area.width,
+                area.y + rng.gen::<f32>() * area.height,
+            );
+            self.particles.push(particle);
+        }
+    }
+    ///spawns 'count' particles at once in 'area', as long as the emitter has room for them
+    pub fn burst(&mut self, rng: &mut impl Rng, area: Rectangle, count: usize) {
+        for _ in 0..count.min(self.emitter.max.saturating_sub(self.particles.len())) {
+            let particle = self.spawn_particle(rng, area);
+            self.particles.push(particle);
+        }
+    }
+    ///advances the particles by 'dt' seconds and spawns new ones in 'area',
+    ///which is in the emitter's space
+    ///
+    ///particles die when their life ends or when they leave 'area' for good
+    pub fn step(&mut self, dt: f32, rng: &mut impl Rng, area: Rectangle) {
+        let lifetime = self.emitter.lifetime;
+        let (min_x, max_x) = (area.x - area.width, area.x + 2.0 * area.width);
+        let (min_y, max_y) = (area.y - area.height, area.y + 2.0 * area.height);
+        self.particles.retain_mut(|p| {
+            p.age += dt;
+            p.position += p.velocity * dt;
+            p.age < lifetime
+                && (min_x..max_x).contains(&p.position.x)
+                && (min_y..max_y).contains(&p.position.y)
+        });
+
+        self.pending += self.emitter.rate * self.rate_factor * dt;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if self.particles.len() < self.emitter.max {
+                let particle = self.spawn_particle(rng, area);
+                self.particles.push(particle);
+            }
+        }
+    }
+    fn spawn_particle(&self, rng: &mut impl Rng, area: Rectangle) -> Particle {
+        let e = &self.emitter;
+        let velocity = Vec2::new(
+            e.velocity.x + (rng.gen::<f32>() * 2.0 - 1.0) * e.jitter.x,
+            e.velocity.y + (rng.gen::<f32>() * 2.0 - 1.0) * e.jitter.y,
+        );
+        let position = match e.spawn {
+            //slanted particles also start left of the area, so its left side is covered too
+            Spawn::Top => {
+                let lead = e.velocity.x.max(0.0) * e.lifetime * 0.5;
+                Vec2::new(area.x - lead + rng.gen::<f32>() * (area.width + lead), area.y)
+            }
+            Spawn::Anywhere => Vec2::new(
+                area.x + rng.gen::<f32>() * area.width,
+                area.y + rng.gen::<f32>() * area.height,
+            ),
+        };
+        Particle { position, velocity, age: 0.0 }
+    }
+    ///draws the particles, fading them out during the last quarter of their life
+    ///and scaling them with the zoom when they are in map space, 'alpha' fades all of them
+    pub fn draw(&self, r: &mut dyn Renderer, camera: &Camera, alpha: f32) {
+        let e = &self.emitter;
+        let zoom = match e.space {
+            Space::Screen => 1.0,
+            Space::Map => camera.scale / SCALE,
+        };
+        for p in &self.particles {
+            let position = match e.space {
+                Space::Screen => p.position,
+                Space::Map => camera.map_to_screen(p.position),
+            };
+            let fade = ((e.lifetime - p.age) / (e.lifetime * 0.25)).clamp(0.0, 1.0);
+            r.draw_sprite(e.sprite, None, DrawParams::new()
+                .position(position)
+                .scale(e.scale * zoom)
+                .color(e.color.with_alpha(e.color.a * fade * alpha)));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::visualizer::renderer::{Image, Renderer, Sprite};
+
+///environment variable that makes [assert_golden] rewrite the snapshots
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+///a single draw call captured by [RecordingRenderer]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Sprite {
+        sprite: Sprite,
+        region: Option<Rectangle>,
+        position: Vec2<f32>,
+        scale: Vec2<f32>,
+        rotation: f32,
+        color: Color,
+    },
+    Text {
+        text: String,
+        position: Vec2<f32>,
+        scale: Vec2<f32>,
+        color: Color,
+    },
+    ///start of the draw calls going to an off-screen layer
+    BeginLayer { id: usize, clear: bool },
+    EndLayer,
+    ///content of a layer drawn on the window
+    Layer(usize),
+    Image {
+        width: usize,
+        height: usize,
+        version: u64,
+        region: Option<Rectangle>,
+        position: Vec2<f32>,
+        scale: Vec2<f32>,
+        color: Color,
+    },
+}
+
+///[Renderer] that records the draw calls instead of issuing them
+pub struct RecordingRenderer {
+    commands: Vec<DrawCommand>,
+    window_size: (f32, f32),
+    ///whether to act as a backend keeping its layers, see [Renderer::keeps_layers]
+    layers: bool,
+}
+
+impl Default for RecordingRenderer {
+    fn default() -> Self {
+        Self::new((WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32))
+    }
+}
+
+impl RecordingRenderer {
+    pub fn new(window_size: (f32, f32)) -> Self {
+        Self {
+            commands: Vec::new(),
+            window_size,
+            layers: false,
+        }
+    }
+    ///records the layer calls too, as a backend keeping the layers between frames
+    pub fn with_layers(mut self) -> Self {
+        self.layers = true;
+        self
+    }
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+    ///returns the commands recorded so far, starting a new frame
+    pub fn take_frame(&mut self) -> Vec<DrawCommand> {
+        std::mem::take(&mut self.commands)
+    }
+    ///textual form of the recorded frame, one command per line
+    ///
+    ///numbers are rounded to two decimals so snapshots are stable,
+    ///rotations and colors are only written when they differ from 0 and white
+    pub fn snapshot(&self) -> String {
+        let mut s = String::new();
+        for command in &self.commands {
+            match command {
+                DrawCommand::Sprite { sprite, region, position, scale, rotation, color } => {
+                    let _ = write!(s, "sprite {:?} {} pos=({:.2}, {:.2}) scale=({:.2}, {:.2})",
+                                   sprite, region_text(region), position.x, position.y, scale.x, scale.y);
+                    if rotation.abs() >= 0.005 {
+                        let _ = write!(s, " rotation={:.2}", rotation);
+                    }
+                    write_color(&mut s, *color);
+                }
+                DrawCommand::Text { text, position, scale, color } => {
+                    let _ = write!(s, "text {:?} pos=({:.2}, {:.2}) scale=({:.2}, {:.2})",
+                                   text, position.x, position.y, scale.x, scale.y);
+                    write_color(&mut s, *color);
+                }
+                DrawCommand::BeginLayer { id, clear } => {
+                    let _ = writeln!(s, "begin_layer {}{}", id, if *clear { " clear" } else { "" });
+                }
+                DrawCommand::EndLayer => s.push_str("end_layer\n"),
+                DrawCommand::Layer(id) => {
+                    let _ = writeln!(s, "layer {}", id);
+                }
+                DrawCommand::Image { width, height, version, region, position, scale, color } => {
+                    let _ = write!(s, "image {}x{} v{} {} pos=({:.2}, {:.2}) scale=({:.2}, {:.2})",
+                                   width, height, version, region_text(region), position.x, position.y, scale.x, scale.y);
+                    write_color(&mut s, *color);
+                }
+            }
+        }
+        s
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn draw_sprite(&mut self, sprite: Sprite, region: Option<Rectangle>, params: DrawParams) {
+        self.commands.push(DrawCommand::Sprite {
+            sprite,
+            region,
+            position: params.position,
+            scale: params.scale,
+            rotation: params.rotation,
+            color: params.color,
+        });
+    }
+    fn draw_text(&mut self, text: &str, params: DrawParams) {
+        self.commands.push(DrawCommand::Text {
+            text: text.to_string(),
+            position: params.position,
+            scale: params.scale,
+            color: params.color,
+        });
+    }
+    fn window_size(&self) -> (f32, f32) {
+        self.window_size
+    }
+    fn keeps_layers(&self) -> bool {
+        self.layers
+    }
+    fn begin_layer(&mut self, id: usize, clear: bool) {
+        if self.layers {
+            self.commands.push(DrawCommand::BeginLayer { id, clear });
+        }
+    }
+    fn end_layer(&mut self) {
+        if self.layers {
+            self.commands.push(DrawCommand::EndLayer);
+        }
+    }
+    fn draw_layer(&mut self, id: usize) {
+        if self.layers {
+            self.commands.push(DrawCommand::Layer(id));
+        }
+    }
+    fn draw_image(&mut self, image: &Image, region: Option<Rectangle>, params: DrawParams) {
+        self.commands.push(DrawCommand::Image {
+            width: image.width(),
+            height: image.height(),
+            version: image.version(),
+            region,
+            position: params.position,
+            scale: params.scale,
+            color: params.color,
+        });
+    }
+}
+
+fn region_text(region: &Option<Rectangle>) -> String {
+    match region {
+        Some(r) => format!("({:.2}, {:.2}, {:.2}, {:.2})", r.x, r.y, r.width, r.height),
+        None => "full".to_string(),
+    }
+}
+
+fn write_color(s: &mut String, color: Color) {
+    if color != Color::WHITE {
+        let _ = write!(s, " color=({:.2}, {:.2}, {:.2}, {:.2})", color.r, color.g, color.b, color.a);
+    }
+    s.push('\n');
+}
+
+///compares 'actual' with the snapshot stored at 'path'
+///
+///when the [UPDATE_GOLDEN] environment variable is set the snapshot is
+///(re)written instead, so intended changes can be accepted
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDEN).is_some() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("failed to create golden directory");
+        }
+        fs::write(path, actual).expect("failed to write golden file");
+        return;
+    }
+    let expected = fs::read_to_string(path).unwrap_or_else(|_| {
+        panic!("missing golden file {}, run with {}=1 to create it", path.display(), UPDATE_GOLDEN)
+    });
+    if expected != actual {
+        let first_diff = expected.lines().zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        panic!("frame differs from {} at line {}\n--- expected\n{}\n--- actual\n{}",
+               path.display(), first_diff + 1,
+               expected.lines().nth(first_diff).unwrap_or("<end>"),
+               actual.lines().nth(first_diff).unwrap_or("<end>"));
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+static NEXT_IMAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+///identifies an image the renderer knows how to draw
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sprite {
+    ///tiles atlas of the given style
+    Tileset(usize),
+    ///contents atlas of the given style
+    Contentset(usize),
+    ///tiles atlas of the given style turned into isometric diamonds, see [crate::visualizer::textures::iso_rect]
+    IsoTileset(usize),
+    ///white isometric diamond, tinted to fill tiles with a color
+    Diamond,
+    Robot,
+    BackpackSlot,
+    Weather(WeatherSprite),
+    ///single white pixel, stretched to draw rectangles and lines
+    Pixel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WeatherSprite {
+    Sun,
+    Fog,
+    Snow,
+    Drop,
+}
+
+///RGBA image built on the cpu, the renderer uploads it again only after it changes
+#[derive(Clone, Debug)]
+pub struct Image {
+    id: u64,
+    version: u64,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    ///creates a transparent [width x height] image
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            version: 0,
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+    ///identifies the image among the others, to cache what was uploaded
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    ///grows every time a pixel is written
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    ///RGBA bytes, row by row
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+    ///sets pixel (x, y), ignoring coordinates outside the image
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let i = (y * self.width + x) * 4;
+        let rgba = [color.r, color.g, color.b, color.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        if self.pixels[i..i + 4] != rgba {
+            self.pixels[i..i + 4].copy_from_slice(&rgba);
+            self.version += 1;
+        }
+    }
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let i = (y * self.width + x) * 4;
+        let p = &self.pixels[i..i + 4];
+        Color::rgba8(p[0], p[1], p[2], p[3])
+    }
+}
+
+///drawing backend used by the visualizer components
+///
+///the components only describe what to draw, so the same code can target
+///tetra, a recording backend or anything else implementing this trait
+pub trait Renderer {
+    ///draws 'region' of 'sprite' (the whole sprite if None)
+    fn draw_sprite(&mut self, sprite: Sprite, region: Option<Rectangle>, params: DrawParams);
+    ///draws a line of text
+    fn draw_text(&mut self, text: &str, params: DrawParams);
+    ///size of the drawable area in pixels
+    fn window_size(&self) -> (f32, f32);
+    ///whether the off-screen layers keep what is drawn in them between frames
+    ///
+    ///when they don't, the layer calls do nothing and everything is drawn every frame
+    fn keeps_layers(&self) -> bool {
+        false
+    }
+    ///sends the next draw calls to the off-screen layer 'id', as big as the window,
+    ///clearing it first if 'clear'
+    fn begin_layer(&mut self, _id: usize, _clear: bool) {}
+    ///goes back to drawing on the window
+    fn end_layer(&mut self) {}
+    ///draws the content of the layer 'id' on the window
+    fn draw_layer(&mut self, _id: usize) {}
+    ///draws 'region' of 'image' (the whole image if None)
+    ///
+    ///by default every pixel is drawn as a rectangle, backends that can keep
+    ///textures around should upload the image once per [Image::version]
+    fn draw_image(&mut self, image: &Image, region: Option<Rectangle>, params: DrawParams) {
+        let region = region.unwrap_or(Rectangle::new(0.0, 0.0, image.width() as f32, image.height() as f32));
+        for y in region.y as usize..(region.y + region.height) as usize {
+            for x in region.x as usize..(region.x + region.width) as usize {
+                let color = image.pixel(x, y);
+                if color.a > 0.0 {
+                    let (dx, dy) = (x as f32 - region.x, y as f32 - region.y);
+                    self.draw_rect(Rectangle::new(params.position.x + dx * params.scale.x,
+                                                  params.position.y + dy * params.scale.y,
+                                                  params.scale.x, params.scale.y), color);
+                }
+            }
+        }
+    }
+    ///fills 'rect' with 'color'
+    fn draw_rect(&mut self, rect: Rectangle, color: Color) {
+        self.draw_sprite(Sprite::Pixel, None, DrawParams::new()
+            .position(Vec2::new(rect.x, rect.y))
+            .scale(Vec2::new(rect.width, rect.height))
+            .color(color));
+    }
+    ///draws a segment 'width' pixels thick from 'from' to 'to'
+    fn draw_line(&mut self, from: Vec2<f32>, to: Vec2<f32>, width: f32, color: Color) {
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        self.draw_sprite(Sprite::Pixel, None, DrawParams::new()
+            .position(from)
+            .origin(Vec2::new(0.0, 0.5))
+            .rotation(dy.atan2(dx))
+            .scale(Vec2::new((dx * dx + dy * dy).sqrt(), width))
+            .color(color));
+    }
+}
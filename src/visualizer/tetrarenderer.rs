@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use tetra::{Context, graphics, window};
+use tetra::graphics::{Canvas, Color, DrawParams, FilterMode, Rectangle, Texture, TextureFormat};
+use tetra::graphics::text::{Font, Text};
+
+use crate::visualizer::renderer::{Image, Renderer, Sprite};
+use crate::visualizer::textures::upload_sprite;
+
+///tetra resources, uploaded once and reused between frames
+pub struct TetraAssets {
+    textures: HashMap<Sprite, Texture>,
+    ///uploaded [Image]s by id, with the version they were uploaded at
+    images: HashMap<u64, (u64, Texture)>,
+    ///off-screen layers by id
+    layers: HashMap<usize, Canvas>,
+    text: Text,
+}
+
+impl TetraAssets {
+    pub fn new(ctx: &mut Context) -> tetra::Result<Self> {
+        Ok(Self {
+            textures: HashMap::new(),
+            images: HashMap::new(),
+            layers: HashMap::new(),
+            text: Text::new("", Font::vector(ctx, "./resources/fonts/roboto.ttf", 17.0)?),
+        })
+    }
+}
+
+///[Renderer] drawing on a tetra window
+pub struct TetraRenderer<'a> {
+    ctx: &'a mut Context,
+    assets: &'a mut TetraAssets,
+}
+
+impl<'a> TetraRenderer<'a> {
+    pub fn new(ctx: &'a mut Context, assets: &'a mut TetraAssets) -> Self {
+        Self { ctx, assets }
+    }
+}
+
+impl Renderer for TetraRenderer<'_> {
+    fn draw_sprite(&mut self, sprite: Sprite, region: Option<Rectangle>, params: DrawParams) {
+        let ctx = &mut *self.ctx;
+        let texture = self.assets.textures
+            .entry(sprite)
+            .or_insert_with(|| upload_sprite(ctx, sprite));
+        match region {
+            Some(r) => texture.draw_region(ctx, r, params),
+            None => texture.draw(ctx, params),
+        }
+    }
+    fn keeps_layers(&self) -> bool {
+        true
+    }
+    fn begin_layer(&mut self, id: usize, clear: bool) {
+        let (w, h) = window::get_size(self.ctx);
+        let ctx = &mut *self.ctx;
+        let canvas = self.assets.layers.entry(id).or_insert_with(|| Canvas::new(ctx, w, h).expect("failed to create layer canvas"));
+        if canvas.width() != w || canvas.height() != h {
+            *canvas = Canvas::new(ctx, w, h).expect("failed to create layer canvas");
+        }
+        graphics::set_canvas(ctx, canvas);
+        if clear {
+            graphics::clear(ctx, Color::rgba(0.0, 0.0, 0.0, 0.0));
+        }
+    }
+    fn end_layer(&mut self) {
+        graphics::reset_canvas(self.ctx);
+    }
+    fn draw_layer(&mut self, id: usize) {
+        if let Some(canvas) = self.assets.layers.get(&id) {
+            canvas.draw(self.ctx, DrawParams::new());
+        }
+    }
+    fn draw_image(&mut self, image: &Image, region: Option<Rectangle>, params: DrawParams) {
+        let ctx = &mut *self.ctx;
+        let (version, texture) = match self.assets.images.remove(&image.id()) {
+            Some((version, texture)) if version == image.version() => (version, texture),
+            Some((stale, texture)) if texture.width() as usize == image.width() && texture.height() as usize == image.height() => {
+                //a failed upload is skipped for this frame and tried again on the next one
+                if texture.replace_data(ctx, image.pixels()).is_err() {
+                    self.assets.images.insert(image.id(), (stale, texture));
+                    return;
+                }
+                (image.version(), texture)
+            }
+            _ => {
+                let mut texture = match Texture::from_data(ctx, image.width() as i32, image.height() as i32,
+                                                           TextureFormat::Rgba8, image.pixels()) {
+                    Ok(texture) => texture,
+                    Err(_) => return,
+                };
+                texture.set_filter_mode(ctx, FilterMode::Nearest);
+                (image.version(), texture)
+            }
+        };
+        match region {
+            Some(r) => texture.draw_region(ctx, r, params),
+            None => texture.draw(ctx, params),
+        }
+        self.assets.images.insert(image.id(), (version, texture));
+    }
+    fn draw_text(&mut self, text: &str, params: DrawParams) {
+        self.assets.text.set_content(text);
+        self.assets.text.draw(self.ctx, params);
+    }
+    fn window_size(&self) -> (f32, f32) {
+        let (w, h) = window::get_size(self.ctx);
+        (w as f32, h as f32)
+    }
+}
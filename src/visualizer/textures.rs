@@ -1,9 +1,14 @@
 use robotics_lib::world::environmental_conditions::WeatherType;
 use tetra::{Context};
-use tetra::graphics::{DrawParams, Rectangle, Texture};
+use tetra::graphics::{Color, ImageData, Rectangle, Texture, TextureFormat};
 use robotics_lib::world::tile::{Content, TileType};
 use robotics_lib::world::tile::TileType::*;
 
+use crate::visualizer::PIXEL;
+use crate::visualizer::contents::{self, Rgb};
+pub use crate::visualizer::contents::{content_name, content_order};
+use crate::visualizer::renderer::{Sprite, WeatherSprite};
+
 
 pub fn upload_tileset(ctx : &mut Context, selection : usize) -> Texture{
     match selection {
@@ -11,23 +16,89 @@ pub fn upload_tileset(ctx : &mut Context, selection : usize) -> Texture{
         _ => Texture::new(ctx, "./resources/tileset/ts_minecraft.png")
     }.expect("failed to upload texture")
 }
+///uploads the tileset of 'selection' with every tile turned into an isometric diamond
+pub fn upload_iso_tileset(ctx : &mut Context, selection : usize) -> Texture{
+    let path = match selection {
+        1 => "./resources/tileset/tileset.png",
+        _ => "./resources/tileset/ts_minecraft.png"
+    };
+    let image = ImageData::from_file(path).expect("failed to load tileset");
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let data = iso_atlas(image.as_bytes(), width, height, PIXEL as usize);
+    Texture::from_data(ctx, width as i32 * 2, height as i32, TextureFormat::Rgba8, &data)
+        .expect("failed to upload isometric tileset")
+}
 pub fn upload_contentset(ctx : &mut Context, selection : usize) -> Texture{
     match selection {
         1 => Texture::new(ctx, "./resources/contentset/contentset.png"),
         _ => Texture::new(ctx, "./resources/contentset/cs_minecraft.png")
     }.expect("failed to upload contentset")
 }
+///uploads the texture behind a sprite
+pub fn upload_sprite(ctx : &mut Context, sprite : Sprite) -> Texture{
+    match sprite {
+        Sprite::Tileset(style) => upload_tileset(ctx, style),
+        Sprite::Contentset(style) => upload_contentset(ctx, style),
+        Sprite::IsoTileset(style) => upload_iso_tileset(ctx, style),
+        Sprite::Diamond => {
+            let cell = PIXEL as usize;
+            let data = iso_atlas(&vec![255; cell * cell * 4], cell, cell, cell);
+            Texture::from_data(ctx, cell as i32 * 2, cell as i32, TextureFormat::Rgba8, &data)
+                .expect("failed to upload diamond texture")
+        }
+        Sprite::Robot => Texture::new(ctx, "./resources/robot.png")
+            .expect("failed to upload robot image"),
+        Sprite::BackpackSlot => Texture::new(ctx, "./resources/backpack_void.png")
+            .expect("failed to upload inventory's square image"),
+        Sprite::Weather(w) => {
+            match w {
+                WeatherSprite::Sun => {Texture::new(ctx, "./resources/weather/sun.png")}
+                WeatherSprite::Fog => {Texture::new(ctx, "./resources/weather/fog.png")}
+                WeatherSprite::Snow => {Texture::new(ctx, "./resources/weather/snow.png")}
+                WeatherSprite::Drop => {Texture::new(ctx, "./resources/weather/drop.png")}
+            }.expect("failed to upload weather texture")
+        }
+        Sprite::Pixel => Texture::from_data(ctx, 1, 1, TextureFormat::Rgba8, &[255; 4])
+            .expect("failed to upload pixel texture"),
+    }
+}
 
-fn draw_rect(texture: Texture, ctx : &mut Context, region : Rectangle, draw_params: DrawParams){
-    texture.draw_region(ctx, region, draw_params)
+///turns every 'cell' sized square of a [width x height] RGBA atlas into a diamond
+///twice as wide as high, the resulting atlas is twice as wide as the original
+///
+///the x axis of the squares runs down to the right in the diamonds, the y axis down to the left
+pub fn iso_atlas(rgba: &[u8], width: usize, height: usize, cell: usize) -> Vec<u8> {
+    let mut out = vec![0; width * 2 * height * 4];
+    for y in 0..height {
+        for x in 0..width * 2 {
+            let (cell_x, cell_y) = (x / (cell * 2), y / cell);
+            //position inside the diamond, along its two diagonals
+            let diff = (x % (cell * 2)) as f32 + 0.5 - cell as f32;
+            let sum = ((y % cell) as f32 + 0.5) * 2.0;
+            let (u, v) = ((sum + diff) / 2.0, (sum - diff) / 2.0);
+            if u < 0.0 || v < 0.0 || u >= cell as f32 || v >= cell as f32 {
+                continue;
+            }
+            let src = ((cell_y * cell + v as usize) * width + cell_x * cell + u as usize) * 4;
+            let dst = (y * width * 2 + x) * 4;
+            out[dst..dst + 4].copy_from_slice(&rgba[src..src + 4]);
+        }
+    }
+    out
+}
+///region of [Sprite::IsoTileset] matching 'rect' in the square tileset
+pub fn iso_rect(rect: Rectangle) -> Rectangle {
+    Rectangle::new(rect.x * 2.0, rect.y, rect.width * 2.0, rect.height)
 }
+
 pub trait Drawable {
-    fn draw(&self,texture : Texture, ctx : &mut Context, draw_params: DrawParams);
+    ///region of the object inside its atlas (tileset or contentset)
+    fn atlas_rect(&self) -> Rectangle;
 }
 
 impl Drawable for TileType {
-    fn draw(&self, texture : Texture, ctx: &mut Context, draw_params: DrawParams) {
-        let r = match self {
+    fn atlas_rect(&self) -> Rectangle {
+        match self {
             DeepWater => {Rectangle::new(0.0,0.0,64.0,64.0)}
             ShallowWater => {Rectangle::new(64.0,0.0,64.0,64.0)}
             Sand => {Rectangle::new(128.0,0.0,64.0,64.0)}
@@ -39,14 +110,13 @@ impl Drawable for TileType {
             Lava => {Rectangle::new(0.0,128.0,64.0,64.0)}
             Teleport(_) => {Rectangle::new(64.0,128.0,64.0,64.0)}
             Wall => {Rectangle::new(128.0,128.0,64.0,64.0)}
-        };
-        draw_rect(texture, ctx, r, draw_params)
+        }
     }
 }
 
 impl Drawable for Content {
-    fn draw(&self, texture: Texture, ctx: &mut Context, draw_params: DrawParams) {
-        let r = match self {
+    fn atlas_rect(&self) -> Rectangle {
+        match self {
             Content::Rock(_) => {Rectangle::new(0.0,0.0,64.0,64.0)}
             Content::Tree(_) => {Rectangle::new(64.0,0.0,64.0,64.0)}
             Content::Garbage(_) => {Rectangle::new(128.0,0.0,64.0,64.0)}
@@ -61,22 +131,36 @@ impl Drawable for Content {
             Content::Bush(_) => {Rectangle::new(192.0,128.0,64.0,64.0)}
             Content::Scarecrow => {Rectangle::new(0.0,192.0,64.0,64.0)}
             _ => {Rectangle::new(192.0,192.0,64.0,64.0)}
-        };
-        draw_rect(texture, ctx, r, draw_params)
+        }
     }
 }
 pub(crate) trait Texturizable {
-    ///matches an object with its texture
-    fn get_texture(&self, ctx: &mut Context) -> Texture;
+    ///matches an object with its sprite
+    fn get_sprite(&self) -> Sprite;
 }
 impl Texturizable for WeatherType {
-    fn get_texture(&self, ctx: &mut Context) -> Texture {
+    fn get_sprite(&self) -> Sprite {
         let tmp = match self {
-            WeatherType::Sunny => {Texture::new(ctx, "./resources/weather/sun.png")}
-            WeatherType::Foggy => {Texture::new(ctx, "./resources/weather/fog.png")}
-            WeatherType::TrentinoSnow => {Texture::new(ctx, "./resources/weather/snow.png")}
-            _ => {Texture::new(ctx, "./resources/weather/drop.png")}
+            WeatherType::Sunny => {WeatherSprite::Sun}
+            WeatherType::Foggy => {WeatherSprite::Fog}
+            WeatherType::TrentinoSnow => {WeatherSprite::Snow}
+            _ => {WeatherSprite::Drop}
         };
-        tmp.expect("failed to upload weather texture")
+        Sprite::Weather(tmp)
     }
-}
\ No newline at end of file
+}
+///[contents::robot_tint] as a tetra color
+pub fn robot_tint(id: usize) -> Color {
+    color(contents::robot_tint(id))
+}
+///[contents::tile_color] as a tetra color
+pub fn tile_color(tile_type: TileType) -> Color {
+    color(contents::tile_color(tile_type))
+}
+///[contents::content_color] as a tetra color
+pub fn content_color(content: &Content) -> Color {
+    color(contents::content_color(content))
+}
+fn color(rgb: Rgb) -> Color {
+    Color::rgb(rgb.r, rgb.g, rgb.b)
+}
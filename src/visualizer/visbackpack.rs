@@ -1,91 +1,161 @@
-use std::collections::HashMap;
-
 use robotics_lib::world::tile::Content;
-use tetra::Context;
-use tetra::graphics::{DrawParams, Texture};
-use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{Color, DrawParams, Rectangle};
 use tetra::math::Vec2;
 
-use crate::visualizer::{BP_SCALE, PIXEL, SCALE};
-use crate::visualizer::textures::{Drawable, upload_contentset};
+use crate::visualizer::{BP_SCALE, PIXEL};
+use crate::visualizer::renderer::{Renderer, Sprite};
+use crate::visualizer::textures::{content_name, content_order, Drawable};
+use crate::visualizer::visstate::RobotState;
+
+///room left of the slots for the "used/total" indicator
+const CAPACITY_WIDTH: f32 = 60.0;
+///fill bar under the "used/total" text
+const FILL_BAR: Rectangle = Rectangle::new(4.0, 22.0, 48.0, 5.0);
+const FILL_BAR_BACKGROUND: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const FILL_COLOR: Color = Color::rgb(0.4, 0.8, 0.4);
+const FULL_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+///seconds a change stays visible
+const CHANGE_DURATION: f32 = 1.5;
+///pixels a change label rises during its life
+const LABEL_RISE: f32 = 24.0;
 
-const OFFSET: f32 = 0.5;
+///a backpack change being animated
+struct SlotChange {
+    robot: usize,
+    content: Content,
+    delta: i64,
+    ///seconds since the change arrived
+    age: f32,
+}
 
 ///visualizable backpack structure
+///
+///slots are laid out in a fixed order of content kind, followed by empty
+///slots up to the backpack size, wrapping when the window is too narrow.
+///how full the backpack is is shown by a counter and a bar on the left of the slots.
+///changes are shown for a moment with a floating label and a tinted slot
 pub struct VisBackPack {
-    square: Texture,
-    size: usize,
-    contents: HashMap<Content, usize>,
-
-    text: Text,
     scale: f32,
+    changes: Vec<SlotChange>,
+}
+
+impl Default for VisBackPack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VisBackPack {
-    pub fn new(ctx: &mut Context, size: usize) -> Self {
+    pub fn new() -> Self {
         Self {
-            square: Texture::new(ctx, "./resources/backpack_void.png").expect("failed to upload inventory's square image"),
-            size,
-            contents: HashMap::new(),
             scale: BP_SCALE,
-            text: Text::new(format!(""),
-                            Font::vector(ctx, "./resources/fonts/roboto.ttf", 17.0)
-                                .expect("failed to upload font")),
+            changes: Vec::new(),
         }
     }
-    pub fn draw(&mut self, ctx: &mut Context, pos: (f32, f32), style: usize) {
-        let mut x = pos.0;
-
-        let texture = upload_contentset(ctx, style);
+    ///starts the animation of the 'deltas' of robot 'id'
+    pub fn notify(&mut self, id: usize, deltas: &[(Content, i64)]) {
+        for (content, delta) in deltas {
+            self.changes.retain(|c| c.robot != id || c.content != *content);
+            self.changes.push(SlotChange { robot: id, content: content.clone(), delta: *delta, age: 0.0 });
+        }
+    }
+    ///advances the animations by 'dt' seconds, dropping the finished ones
+    pub fn update(&mut self, dt: f32) {
+        for change in self.changes.iter_mut() {
+            change.age += dt;
+        }
+        self.changes.retain(|c| c.age < CHANGE_DURATION);
+    }
+    ///size in pixels of a slot
+    pub fn slot_size(&self) -> f32 {
+        PIXEL * self.scale
+    }
+    ///number of slots that fit in a row starting at 'x'
+    pub fn slots_per_row(&self, x: f32, window_width: f32) -> usize {
+        (((window_width - x - CAPACITY_WIDTH) / self.slot_size()).floor() as usize).max(1)
+    }
+    ///top left corner of slot 'i' for a panel drawn at 'pos'
+    pub fn slot_position(&self, i: usize, pos: (f32, f32), per_row: usize) -> (f32, f32) {
+        (
+            pos.0 + CAPACITY_WIDTH + (i % per_row) as f32 * self.slot_size(),
+            pos.1 + (i / per_row) as f32 * self.slot_size(),
+        )
+    }
+    ///draws the backpack of robot 'id' with its running animations
+    pub fn draw(&self, r: &mut dyn Renderer, id: usize, robot: &RobotState, pos: (f32, f32), style: usize) {
         let diff = 0.12;
         let content_scale = self.scale - diff;
-        let to_center_pos = pos.0 + diff;
+        let to_center = diff * PIXEL / 2.0;
 
-        let mut voids = 0;
+        let items = robot.backpack_items();
+        let slots = robot.backpack_size().max(items.len());
+        let per_row = self.slots_per_row(pos.0, r.window_size().0);
 
-        for (cont, quantity) in &self.contents {
-            self.text.set_content(format!("{}", quantity));
-            if quantity > &0usize {
-                self.square.draw(
-                    ctx,
-                    DrawParams::new()
-                        .position(Vec2::new(x + pos.0, pos.1))
-                        .scale(Vec2::new(self.scale, self.scale)),
-                );
+        let (used, size) = (robot.backpack_used(), robot.backpack_size());
+        r.draw_text(
+            &format!("{}/{}", used, size),
+            DrawParams::new().position(Vec2::new(pos.0 + 4.0, pos.1 + 6.0)),
+        );
+        let bar = Rectangle::new(pos.0 + FILL_BAR.x, pos.1 + FILL_BAR.y, FILL_BAR.width, FILL_BAR.height);
+        r.draw_rect(bar, FILL_BAR_BACKGROUND);
+        if used > 0 {
+            let fill = if size == 0 { 1.0 } else { (used as f32 / size as f32).min(1.0) };
+            r.draw_rect(Rectangle::new(bar.x, bar.y, bar.width * fill, bar.height),
+                        if used >= size { FULL_COLOR } else { FILL_COLOR });
+        }
+
+        for i in 0..slots {
+            let (x, y) = self.slot_position(i, pos, per_row);
+            r.draw_sprite(
+                Sprite::BackpackSlot,
+                None,
+                DrawParams::new()
+                    .position(Vec2::new(x, y))
+                    .scale(Vec2::new(self.scale, self.scale)),
+            );
 
-                cont.draw(
-                    texture.clone(),
-                    ctx,
+            if let Some((cont, quantity)) = items.get(i) {
+                r.draw_sprite(
+                    Sprite::Contentset(style),
+                    Some(cont.atlas_rect()),
                     DrawParams::new()
-                        .position(Vec2::new(x + to_center_pos + pos.0, pos.1 + to_center_pos))
+                        .position(Vec2::new(x + to_center, y + to_center))
                         .scale(Vec2::new(content_scale, content_scale)),
                 );
-                self.text.draw(
-                    ctx,
+                r.draw_text(
+                    &format!("{}", quantity),
                     DrawParams::new()
-                        .position(Vec2::new(x + pos.0, pos.1))
+                        .position(Vec2::new(x, y))
                         .scale(Vec2::new(self.scale * 2.0, self.scale * 2.0)),
                 );
-                x += PIXEL * self.scale;
-            }
-            else {
-                voids +=1;
             }
         }
 
-        for _ in 0..voids {
-            self.square.draw(
-                ctx,
+        for change in self.changes.iter().filter(|c| c.robot == id) {
+            //a kind that has just run out has no slot left, its label goes where it would be
+            let order = content_order(&change.content);
+            let i = items.iter().position(|(c, _)| content_order(c) >= order).unwrap_or(items.len());
+            let (x, y) = self.slot_position(i, pos, per_row);
+            let progress = change.age / CHANGE_DURATION;
+            let color = if change.delta > 0 { Color::rgb(0.4, 1.0, 0.4) } else { Color::rgb(1.0, 0.4, 0.4) };
+
+            if i < slots {
+                r.draw_sprite(
+                    Sprite::BackpackSlot,
+                    None,
+                    DrawParams::new()
+                        .position(Vec2::new(x, y))
+                        .scale(Vec2::new(self.scale, self.scale))
+                        .color(color.with_alpha(0.5 * (1.0 - progress))),
+                );
+            }
+            r.draw_text(
+                &format!("{:+} {}", change.delta, content_name(&change.content)),
                 DrawParams::new()
-                    .position(Vec2::new(x + pos.0, pos.1))
-                    .scale(Vec2::new(self.scale, self.scale)),
+                    .position(Vec2::new(x, y + self.slot_size() - LABEL_RISE * progress))
+                    .scale(Vec2::new(0.8, 0.8))
+                    .color(color.with_alpha(1.0 - progress)),
             );
-            x += PIXEL * self.scale;
         }
     }
-    pub fn update(&mut self, new_backpack: HashMap<Content, usize>) {
-        //println!("backpack has been updated!");
-        self.size = new_backpack.len();
-        self.contents = new_backpack;
-    }
-}
\ No newline at end of file
+}
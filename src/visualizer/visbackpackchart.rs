@@ -0,0 +1,105 @@
+use robotics_lib::world::tile::Content;
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::renderer::{Renderer, Sprite};
+use crate::visualizer::textures::{content_color, content_name, content_order, Drawable};
+use crate::visualizer::visstate::RobotState;
+
+const CHART_WIDTH: f32 = 360.0;
+const CHART_HEIGHT: f32 = 160.0;
+const MARGIN: f32 = 10.0;
+const LEGEND_LINE: f32 = 22.0;
+const ICON_SCALE: f32 = 0.3;
+
+///stacked line chart of the backpack contents over time
+///
+///each line is the running total of its content kind and the ones below it,
+///so the top line is the whole backpack
+#[derive(Default)]
+pub struct VisBackpackChart {
+    visible: bool,
+}
+
+impl VisBackpackChart {
+    pub fn new() -> Self {
+        Self { visible: false }
+    }
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    ///draws the chart of 'robot' in the bottom left corner, with a legend on its right
+    pub fn draw(&self, r: &mut dyn Renderer, robot: &RobotState, style: usize) {
+        if !self.visible {
+            return;
+        }
+        let history = robot.backpack_history();
+        let (x, y) = (MARGIN, r.window_size().1 - MARGIN - CHART_HEIGHT);
+        r.draw_rect(Rectangle::new(x, y, CHART_WIDTH, CHART_HEIGHT), Color::rgba(0.0, 0.0, 0.0, 0.6));
+        r.draw_text(&format!("{} backpack", robot.name()), DrawParams::new()
+            .position(Vec2::new(x + 4.0, y + 2.0))
+            .scale(Vec2::new(0.7, 0.7)));
+        let (first, last) = match (history.first(), history.last()) {
+            (Some(f), Some(l)) => (f.0, l.0),
+            _ => return,
+        };
+
+        let mut kinds: Vec<&Content> = Vec::new();
+        for (_, items) in history {
+            for (c, _) in items {
+                if !kinds.contains(&c) {
+                    kinds.push(c);
+                }
+            }
+        }
+        kinds.sort_by_key(|c| content_order(c));
+        let max_total = history.iter()
+            .map(|(_, items)| items.iter().map(|(_, q)| *q).sum::<usize>())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let span = (last - first).max(1) as f32;
+
+        //one point per pixel at most, long runs are sampled
+        let stride = (history.len() as f32 / CHART_WIDTH).ceil().max(1.0) as usize;
+        let mut samples: Vec<&(usize, Vec<(Content, usize)>)> = history.iter().step_by(stride).collect();
+        if samples.last().map(|s| s.0) != Some(last) {
+            samples.push(history.last().unwrap());
+        }
+
+        let to_screen = |step: usize, total: usize| Vec2::new(
+            x + (step - first) as f32 / span * CHART_WIDTH,
+            y + CHART_HEIGHT - total as f32 / max_total as f32 * (CHART_HEIGHT - 20.0),
+        );
+        for (k, kind) in kinds.iter().enumerate() {
+            let running_total = |items: &Vec<(Content, usize)>| items.iter()
+                .filter(|(c, _)| kinds[..=k].contains(&c))
+                .map(|(_, q)| *q)
+                .sum::<usize>();
+            let color = content_color(kind);
+            for pair in samples.windows(2) {
+                r.draw_line(to_screen(pair[0].0, running_total(&pair[0].1)),
+                            to_screen(pair[1].0, running_total(&pair[1].1)), 2.0, color);
+            }
+
+            let (lx, ly) = (x + CHART_WIDTH + 8.0, y + k as f32 * LEGEND_LINE);
+            r.draw_rect(Rectangle::new(lx, ly + 4.0, 4.0, 12.0), color);
+            r.draw_sprite(Sprite::Contentset(style), Some(kind.atlas_rect()), DrawParams::new()
+                .position(Vec2::new(lx + 8.0, ly))
+                .scale(Vec2::new(ICON_SCALE, ICON_SCALE)));
+            r.draw_text(content_name(kind), DrawParams::new()
+                .position(Vec2::new(lx + 30.0, ly + 2.0))
+                .scale(Vec2::new(0.7, 0.7)));
+        }
+
+        r.draw_text(&format!("{}", max_total), DrawParams::new()
+            .position(Vec2::new(x + CHART_WIDTH - 30.0, y + 2.0))
+            .scale(Vec2::new(0.7, 0.7)));
+        r.draw_text(&format!("step {} - {}", first, last), DrawParams::new()
+            .position(Vec2::new(x + 4.0, y + CHART_HEIGHT - 16.0))
+            .scale(Vec2::new(0.7, 0.7)));
+    }
+}
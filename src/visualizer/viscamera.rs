@@ -0,0 +1,195 @@
+use std::ops::Range;
+
+use tetra::graphics::Rectangle;
+use tetra::math::Vec2;
+
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::visualizer::{PIXEL, SCALE, TOP_OFFSET};
+
+const ZOOM_IN_STEP: f32 = 0.1;
+const ZOOM_OUT_STEP: f32 = 0.05;
+///below this scale zooming multiplies by [FINE_ZOOM] instead of adding steps
+const FINE_ZOOM_BELOW: f32 = 0.1;
+const FINE_ZOOM: f32 = 0.75;
+///smallest scale, a tile is still a bit more than a pixel wide
+pub const MIN_SCALE: f32 = 0.02;
+///pixels at zoom 1 an isometric tile is lifted per level of elevation
+const ELEVATION_STEP: f32 = 4.0;
+///elevation above which isometric tiles are not lifted any further
+pub const MAX_ELEVATION: usize = 16;
+
+///how the tiles are laid out on screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Projection {
+    ///square tiles, rows growing to the right and columns downwards
+    #[default]
+    TopDown,
+    ///diamond tiles, rows growing down to the right and columns down to the left,
+    ///lifted by their elevation
+    Isometric,
+}
+
+///position, zoom and projection of the map on screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    ///screen position of the map origin
+    pub map_pos: (f32, f32),
+    pub scale: f32,
+    pub projection: Projection,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            map_pos: (0.0, 0.0),
+            scale: SCALE,
+            projection: Projection::TopDown,
+        }
+    }
+}
+
+impl Camera {
+    ///size in pixels of a tile at the current zoom
+    pub fn tile_size(&self) -> f32 {
+        PIXEL * self.scale
+    }
+    ///moves the map by (dx, dy) tiles, positive values move it right and down
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.map_pos.0 += dx * self.tile_size();
+        self.map_pos.1 += dy * self.tile_size();
+    }
+    pub fn zoom_in(&mut self) {
+        let step = self.tile_size();
+        self.scale = if self.scale < FINE_ZOOM_BELOW {
+            (self.scale / FINE_ZOOM).min(FINE_ZOOM_BELOW)
+        } else {
+            self.scale + ZOOM_IN_STEP
+        };
+        self.map_pos.0 -= step;
+        self.map_pos.1 -= step;
+    }
+    pub fn zoom_out(&mut self) {
+        let step = self.tile_size();
+        self.scale = if self.scale <= FINE_ZOOM_BELOW {
+            (self.scale * FINE_ZOOM).max(MIN_SCALE)
+        } else {
+            (self.scale - ZOOM_OUT_STEP).max(FINE_ZOOM_BELOW)
+        };
+        self.map_pos.0 -= step;
+        self.map_pos.1 -= step;
+    }
+    ///moves the camera towards the given tile
+    pub fn focus(&mut self, tile: (usize, usize)) {
+        let ts = self.tile_size();
+        let (row, col) = (tile.0 as f32, tile.1 as f32);
+        self.map_pos = match self.projection {
+            Projection::TopDown => (-(ts * row) / 2.0, -(ts * col) / 2.0),
+            //the tile ends up in the middle of the window
+            Projection::Isometric => (
+                WINDOW_WIDTH as f32 / 2.0 - (row - col) * ts,
+                (WINDOW_HEIGHT as f32 - TOP_OFFSET) / 2.0 - (row + col) * ts / 2.0,
+            ),
+        };
+    }
+    ///switches between the top-down and the isometric projection
+    pub fn toggle_projection(&mut self) {
+        self.projection = match self.projection {
+            Projection::TopDown => Projection::Isometric,
+            Projection::Isometric => Projection::TopDown,
+        };
+    }
+    ///screen position of the top left corner of tile [row][col]
+    ///
+    ///top-down, rows grow to the right and columns downwards, as in the discovered map.
+    ///isometric, it is the corner of the tile sized square centered on the diamond,
+    ///which spans half a tile more on both sides, not lifted by the elevation
+    pub fn tile_to_screen(&self, tile: (usize, usize)) -> (f32, f32) {
+        self.tile_pos_to_screen((tile.0 as f32, tile.1 as f32))
+    }
+    ///as [Camera::tile_to_screen], for positions between tiles
+    pub fn tile_pos_to_screen(&self, (row, col): (f32, f32)) -> (f32, f32) {
+        let ts = self.tile_size();
+        match self.projection {
+            Projection::TopDown => (
+                self.map_pos.0 + row * ts,
+                TOP_OFFSET + self.map_pos.1 + col * ts,
+            ),
+            Projection::Isometric => (
+                self.map_pos.0 + (row - col) * ts - ts / 2.0,
+                TOP_OFFSET + self.map_pos.1 + (row + col) * ts / 2.0,
+            ),
+        }
+    }
+    ///tile coordinates, with the fraction, under the screen point 'point',
+    ///ignoring the elevation
+    pub fn screen_to_tile(&self, point: Vec2<f32>) -> (f32, f32) {
+        let ts = self.tile_size();
+        let (x, y) = (point.x - self.map_pos.0, point.y - TOP_OFFSET - self.map_pos.1);
+        match self.projection {
+            Projection::TopDown => (x / ts, y / ts),
+            Projection::Isometric => {
+                //row - col and row + col, along the diamond's diagonals
+                let (diff, sum) = (x / ts, y / (ts / 2.0));
+                ((sum + diff) / 2.0, (sum - diff) / 2.0)
+            }
+        }
+    }
+    ///pixels a tile of 'elevation' is lifted by, only in the isometric projection
+    pub fn elevation_lift(&self, elevation: usize) -> f32 {
+        match self.projection {
+            Projection::TopDown => 0.0,
+            Projection::Isometric => elevation.min(MAX_ELEVATION) as f32 * ELEVATION_STEP * self.scale,
+        }
+    }
+    ///rows and columns of the tiles at least partially shown in a window of 'window_size',
+    ///below the top offset, in a [world_size x world_size] world
+    ///
+    ///isometric, the rows and columns of the diamonds around the window are included too
+    pub fn visible_tiles(&self, window_size: (f32, f32), world_size: usize) -> (Range<usize>, Range<usize>) {
+        if self.projection == Projection::Isometric {
+            return self.visible_iso_tiles(window_size, world_size);
+        }
+        let tile_size = self.tile_size();
+        let range = |origin: f32, length: f32| {
+            let first = (-origin / tile_size).floor().max(0.0) as usize;
+            let last = ((length - origin) / tile_size).ceil().max(0.0) as usize;
+            first.min(world_size)..last.min(world_size)
+        };
+        (
+            range(self.map_pos.0, window_size.0),
+            range(self.map_pos.1, (window_size.1 - TOP_OFFSET).max(0.0)),
+        )
+    }
+    fn visible_iso_tiles(&self, window_size: (f32, f32), world_size: usize) -> (Range<usize>, Range<usize>) {
+        //tiles lifted from below the window can still show up
+        let bottom = window_size.1 + self.elevation_lift(MAX_ELEVATION);
+        let corners = [(0.0, TOP_OFFSET), (window_size.0, TOP_OFFSET), (0.0, bottom), (window_size.0, bottom)]
+            .map(|(x, y)| self.screen_to_tile(Vec2::new(x, y)));
+        let range = |values: [f32; 4]| {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let first = (min.floor() - 1.0).max(0.0) as usize;
+            let last = (max.ceil() + 1.0).max(0.0) as usize;
+            first.min(world_size)..last.min(world_size)
+        };
+        (range(corners.map(|c| c.0)), range(corners.map(|c| c.1)))
+    }
+    ///screen position of a point in map pixels at zoom 1, where a tile is [PIXEL] wide
+    pub fn map_to_screen(&self, point: Vec2<f32>) -> Vec2<f32> {
+        Vec2::new(
+            self.map_pos.0 + point.x * self.scale,
+            TOP_OFFSET + self.map_pos.1 + point.y * self.scale,
+        )
+    }
+    ///part of the map, in map pixels at zoom 1, shown in a window of 'window_size'
+    ///
+    ///map pixels are always laid out top-down, the weather doesn't follow the projection
+    pub fn visible_map_area(&self, window_size: (f32, f32)) -> Rectangle {
+        Rectangle::new(
+            -self.map_pos.0 / self.scale,
+            -self.map_pos.1 / self.scale,
+            window_size.0 / self.scale,
+            (window_size.1 - TOP_OFFSET).max(0.0) / self.scale,
+        )
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::renderer::Renderer;
+pub use crate::visualizer::visstate::Severity;
+use crate::visualizer::visstate::VisState;
+
+///messages kept before the oldest are dropped
+const MAX_ENTRIES: usize = 200;
+///wrapped lines shown at once
+const VISIBLE_LINES: usize = 8;
+const LINE_HEIGHT: f32 = 16.0;
+pub(crate) const TEXT_SCALE: f32 = 0.8;
+///average width of a character at [TEXT_SCALE], used to wrap lines
+pub(crate) const CHAR_WIDTH: f32 = 7.0;
+const PANEL_WIDTH: f32 = 330.0;
+const MARGIN: f32 = 10.0;
+
+impl Severity {
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::rgb(0.85, 0.85, 0.85),
+            Severity::Warning => Color::rgb(1.0, 0.8, 0.25),
+            Severity::Error => Color::rgb(1.0, 0.35, 0.3),
+        }
+    }
+}
+
+///a message shown in the console
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleEntry {
+    ///time since the visualizer started
+    pub time: Duration,
+    pub step: usize,
+    ///robot that sent the message, None for the visualizer's own
+    pub robot: Option<usize>,
+    pub severity: Severity,
+    pub text: String,
+}
+
+///an entry with its text already formatted and wrapped to the console width
+struct Logged {
+    entry: ConsoleEntry,
+    lines: Vec<String>,
+}
+
+///bounded and scrollable history of the messages, drawn in the bottom right corner
+///
+///when collapsed only the latest message is shown
+pub struct VisConsole {
+    entries: VecDeque<Logged>,
+    ///wrapped lines of all the entries
+    line_count: usize,
+    ///wrapped lines skipped from the newest one
+    scroll: usize,
+    collapsed: bool,
+}
+
+impl Default for VisConsole {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VisConsole {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            line_count: 0,
+            scroll: 0,
+            collapsed: false,
+        }
+    }
+    ///adds 'entry', naming its robot as it is called in 'state' now
+    ///
+    ///when scrolled back the shown lines stay in place
+    pub fn push(&mut self, entry: ConsoleEntry, state: &VisState) {
+        let lines = wrap(&format(&entry, state), max_chars());
+        self.line_count += lines.len();
+        if self.scroll > 0 {
+            self.scroll += lines.len();
+        }
+        self.entries.push_back(Logged { entry, lines });
+        if self.entries.len() > MAX_ENTRIES {
+            if let Some(oldest) = self.entries.pop_front() {
+                self.line_count -= oldest.lines.len();
+            }
+        }
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+    pub fn entries(&self) -> impl Iterator<Item = &ConsoleEntry> {
+        self.entries.iter().map(|l| &l.entry)
+    }
+    pub fn toggle(&mut self) {
+        self.collapsed = !self.collapsed;
+    }
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+    ///scrolls towards older lines, 'lines' can be negative to go back to the newest
+    pub fn scroll(&mut self, lines: i32) {
+        let scroll = self.scroll as i64 + lines as i64;
+        self.scroll = scroll.clamp(0, self.max_scroll() as i64) as usize;
+    }
+    ///wrapped lines currently skipped from the newest one
+    pub fn scrolled(&self) -> usize {
+        self.scroll
+    }
+    fn max_scroll(&self) -> usize {
+        self.line_count.saturating_sub(VISIBLE_LINES)
+    }
+    ///area covered by the console in a window of 'window_size'
+    pub fn area(&self, window_size: (f32, f32)) -> Rectangle {
+        let lines = if self.collapsed { 1 } else { VISIBLE_LINES + 1 };
+        let height = lines as f32 * LINE_HEIGHT + 4.0;
+        Rectangle::new(window_size.0 - PANEL_WIDTH - MARGIN, window_size.1 - MARGIN - height, PANEL_WIDTH, height)
+    }
+    pub fn draw(&self, r: &mut dyn Renderer) {
+        let area = self.area(r.window_size());
+        r.draw_rect(area, Color::rgba(0.0, 0.0, 0.0, 0.6));
+
+        if self.collapsed {
+            let (text, color) = self.entries.back()
+                .map_or(("", Color::WHITE), |l| (l.lines[0].as_str(), l.entry.severity.color()));
+            self.draw_line(r, text, area.x + 4.0, area.y + 2.0, color);
+            return;
+        }
+
+        let title = if self.scroll > 0 { format!("Console (-{})", self.scroll) } else { "Console".to_string() };
+        self.draw_line(r, &title, area.x + 4.0, area.y + 2.0, Color::WHITE);
+
+        //only the visible lines are walked, from the newest up
+        let lines = self.entries.iter().rev()
+            .flat_map(|l| l.lines.iter().rev().map(move |text| (text, l.entry.severity.color())))
+            .skip(self.scroll)
+            .take(VISIBLE_LINES);
+        let mut visible: Vec<(&String, Color)> = lines.collect();
+        visible.reverse();
+        for (i, (text, color)) in visible.into_iter().enumerate() {
+            self.draw_line(r, text, area.x + 4.0, area.y + 2.0 + (i + 1) as f32 * LINE_HEIGHT, color);
+        }
+    }
+    fn draw_line(&self, r: &mut dyn Renderer, text: &str, x: f32, y: f32, color: Color) {
+        r.draw_text(text, DrawParams::new()
+            .position(Vec2::new(x, y))
+            .scale(Vec2::new(TEXT_SCALE, TEXT_SCALE))
+            .color(color));
+    }
+}
+
+fn format(entry: &ConsoleEntry, state: &VisState) -> String {
+    let secs = entry.time.as_secs();
+    let sender = match entry.robot {
+        Some(id) => format!("{}: ", state.robot(id).map_or("?", |rb| rb.name())),
+        None => String::new(),
+    };
+    format!("[{:02}:{:02}] #{} {}{}", secs / 60, secs % 60, entry.step, sender, entry.text)
+}
+
+///characters fitting in a console line
+fn max_chars() -> usize {
+    ((PANEL_WIDTH - 8.0) / CHAR_WIDTH) as usize
+}
+
+///splits 'text' in lines of at most 'max_chars' characters, breaking between words when possible
+pub fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        //words longer than a line are split
+        while word.len() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.len() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
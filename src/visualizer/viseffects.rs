@@ -0,0 +1,153 @@
+use rand::Rng;
+use robotics_lib::world::tile::Content;
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::particles::{Emitter, ParticleSystem, Space, Spawn};
+use crate::visualizer::renderer::{Renderer, Sprite};
+use crate::visualizer::textures::content_color;
+use crate::visualizer::viscamera::Camera;
+use crate::visualizer::visstate::{VisAction, VisState};
+
+///seconds a put item takes to fall into its container
+const DROP_TIME: f32 = 0.4;
+///height the put item falls from, relative to the tile
+const DROP_HEIGHT: f32 = 0.8;
+///size of the put item, relative to the tile
+const DROP_SIZE: f32 = 0.3;
+const SPARKLE_COLOR: Color = Color::rgb(1.0, 0.95, 0.6);
+const FLAME_COLOR: Color = Color::rgb(1.0, 0.5, 0.1);
+const SMOKE_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.7);
+///side of the square particles start in, in pixels at zoom 1 around the middle of the tile
+const BURST_AREA: f32 = 48.0;
+
+///effect of an action on a tile
+struct Effect {
+    action: VisAction,
+    tile: (usize, usize),
+    ///color of the item falling into a container
+    color: Color,
+    ///seconds since the action
+    age: f32,
+    ///positions relative to the middle of the tile
+    particles: Vec<ParticleSystem>,
+}
+
+impl Effect {
+    fn is_over(&self) -> bool {
+        self.age >= DROP_TIME && self.particles.iter().all(|p| p.particles().is_empty())
+    }
+}
+
+///short effects of the robots' actions, drawn on the tiles they happened on
+#[derive(Default)]
+pub struct VisEffects {
+    effects: Vec<Effect>,
+}
+
+impl VisEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///starts the effect of 'action' on 'tile', which held 'content' before
+    pub fn spawn(&mut self, rng: &mut impl Rng, action: VisAction, tile: (usize, usize), content: &Content) {
+        let area = Rectangle::new(-BURST_AREA / 2.0, -BURST_AREA / 2.0, BURST_AREA, BURST_AREA);
+        let particles = bursts(action, content).into_iter()
+            .map(|(emitter, count)| {
+                let mut system = ParticleSystem::new(emitter);
+                system.burst(rng, area, count);
+                system
+            })
+            .collect();
+        self.effects.push(Effect { action, tile, color: content_color(&put_into(content)), age: 0.0, particles });
+    }
+    ///advances the effects by 'dt' seconds, dropping the finished ones
+    pub fn update(&mut self, dt: f32, rng: &mut impl Rng) {
+        //particles leaving this far from the tile are gone anyway
+        let bounds = Rectangle::new(-BURST_AREA * 2.0, -BURST_AREA * 2.0, BURST_AREA * 4.0, BURST_AREA * 4.0);
+        for effect in self.effects.iter_mut() {
+            effect.age += dt;
+            for system in effect.particles.iter_mut() {
+                system.step(dt, rng, bounds);
+            }
+        }
+        self.effects.retain(|e| !e.is_over());
+    }
+    ///effects still running
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+    ///draws the effects on top of their tiles of 'state', scaled with the zoom
+    pub fn draw(&self, r: &mut dyn Renderer, state: &VisState, camera: &Camera) {
+        let tile_size = camera.tile_size();
+        for effect in &self.effects {
+            let (row, col) = effect.tile;
+            let (x_pixel, y_pixel) = camera.tile_to_screen((row, col));
+            let lift = state.tile(row, col).map_or(0.0, |t| camera.elevation_lift(t.elevation));
+            let middle = Vec2::new(x_pixel + tile_size / 2.0, y_pixel + tile_size / 2.0 - lift);
+
+            if effect.action == VisAction::Put && effect.age < DROP_TIME {
+                //falls from above the tile, speeding up
+                let t = effect.age / DROP_TIME;
+                let size = tile_size * DROP_SIZE;
+                let y = middle.y - tile_size * DROP_HEIGHT * (1.0 - t * t);
+                r.draw_rect(Rectangle::new(middle.x - size / 2.0, y - size / 2.0, size, size), effect.color);
+            }
+            for system in &effect.particles {
+                let e = system.emitter();
+                for p in system.particles() {
+                    //fading out during the second half of their life
+                    let fade = ((e.lifetime - p.age) / (e.lifetime * 0.5)).clamp(0.0, 1.0);
+                    r.draw_sprite(e.sprite, None, DrawParams::new()
+                        .position(middle + p.position * camera.scale)
+                        .scale(e.scale * camera.scale)
+                        .color(e.color.with_alpha(e.color.a * fade)));
+                }
+            }
+        }
+    }
+}
+
+///emitters of the particles thrown at once by 'action' on 'content', with how many
+fn bursts(action: VisAction, content: &Content) -> Vec<(Emitter, usize)> {
+    let burst = |velocity: Vec2<f32>, jitter: Vec2<f32>, lifetime: f32, size: f32, color: Color| Emitter {
+        sprite: Sprite::Pixel,
+        space: Space::Map,
+        spawn: Spawn::Anywhere,
+        rate: 0.0,
+        max: usize::MAX,
+        velocity,
+        jitter,
+        lifetime,
+        scale: Vec2::new(size, size),
+        color,
+    };
+    match action {
+        //splinters of wood or stone
+        VisAction::Destroy => vec![
+            (burst(Vec2::new(0.0, -60.0), Vec2::new(90.0, 50.0), 0.6, 16.0, content_color(content)), 12),
+        ],
+        VisAction::Collect => vec![
+            (burst(Vec2::new(0.0, -40.0), Vec2::new(50.0, 30.0), 0.8, 10.0, SPARKLE_COLOR), 10),
+        ],
+        VisAction::Put => Vec::new(),
+        //last flames going up, followed by some slower smoke
+        VisAction::Extinguish => vec![
+            (burst(Vec2::new(0.0, -70.0), Vec2::new(25.0, 20.0), 0.5, 14.0, FLAME_COLOR), 10),
+            (burst(Vec2::new(0.0, -35.0), Vec2::new(20.0, 10.0), 1.2, 20.0, SMOKE_COLOR), 8),
+        ],
+    }
+}
+
+///what goes into the 'container'
+fn put_into(container: &Content) -> Content {
+    match container {
+        Content::Bin(_) => Content::Garbage(0),
+        Content::Bank(_) => Content::Coin(0),
+        Content::Crate(_) => Content::Tree(0),
+        other => other.clone(),
+    }
+}
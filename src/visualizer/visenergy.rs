@@ -1,36 +1,106 @@
-use robotics_lib::energy::Energy;
-use tetra::Context;
-use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{Color, DrawParams, Rectangle};
 use tetra::math::Vec2;
 
-use crate::visualizer::{PIXEL, SCALE};
-use crate::WINDOW_WIDTH;
+use crate::visualizer::HUD_X;
+use crate::visualizer::renderer::Renderer;
+use crate::visualizer::textures::robot_tint;
+use crate::visualizer::vislighting::day_phase;
+use crate::visualizer::visstate::{RobotState, VisState};
+
+const TAB_WIDTH: f32 = 120.0;
+const TABS_Y: f32 = 46.0;
+///robotics_lib's maximum energy level, used until [crate::visualizer::config::VisConfig::max_energy] says otherwise
+pub const MAX_ENERGY: usize = 1000;
+const BAR_WIDTH: f32 = 160.0;
+const BAR_HEIGHT: f32 = 10.0;
+const BAR_Y: f32 = 22.0;
+const SPARKLINE_HEIGHT: f32 = 20.0;
+const CLOCK_Y: f32 = 58.0;
+
+///color of an energy level out of 'max_energy', green when high, yellow when halfway, red when low
+pub fn energy_color(energy: usize, max_energy: usize) -> Color {
+    let ratio = energy as f32 / max_energy as f32;
+    if ratio > 0.5 {
+        Color::rgb(0.3, 0.85, 0.3)
+    } else if ratio > 0.2 {
+        Color::rgb(0.95, 0.8, 0.2)
+    } else {
+        Color::rgb(0.9, 0.25, 0.2)
+    }
+}
 
 ///visualizable texts structure
 pub struct VisEnergy {
-    font: Font,
-    energy_text: Text,
-    rizzler_text: Text,
+    ///energy of a full bar
+    max_energy: usize,
+}
+
+impl Default for VisEnergy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VisEnergy {
-    pub fn new(ctx: &mut Context) -> Self {
-        let f = Font::vector(ctx, "./resources/fonts/roboto.ttf", 17.0)
-            .expect("failed to upload font");
-        Self {
-            font: f.clone(),
-            energy_text: Text::new(format!("Robot Energy : {:?}", Energy::default()), f.clone()),
-            rizzler_text: Text::new("...", f.clone()),
-        }
+    pub fn new() -> Self {
+        Self { max_energy: MAX_ENERGY }
     }
-    pub fn draw(&mut self, ctx: &mut Context) {
-        self.energy_text.draw(ctx, Vec2::new(WINDOW_WIDTH as f32 / 1.25 - PIXEL * SCALE, 0.0));
-        self.rizzler_text.draw(ctx, Vec2::new(10.0, 30.0));
+    ///sets the energy of a full bar, for worlds with another maximum than robotics_lib's
+    pub fn set_max_energy(&mut self, max_energy: usize) {
+        self.max_energy = max_energy;
     }
-    pub fn update_energy(&mut self, new_energy: usize) {
-        self.energy_text.set_content(format!("Robot Energy : {}", new_energy));
+    ///draws the energy of the 'selected' robot and the clock, with a tab per robot when there are more
+    pub fn draw(&self, r: &mut dyn Renderer, state: &VisState, selected: usize) {
+        let robot = state.robot(selected);
+        let x = HUD_X;
+        r.draw_text(&format!("Robot Energy : {}", robot.map_or(0, |rb| rb.energy())), Vec2::new(x, 0.0).into());
+        if let Some(rb) = robot {
+            self.draw_bar(r, rb, x);
+        }
+        if let Some((hour, minute)) = state.time_of_day() {
+            r.draw_text(&format!("{:02}:{:02} {}", hour, minute, day_phase(hour)), DrawParams::new()
+                .position(Vec2::new(x, CLOCK_Y))
+                .scale(Vec2::new(0.8, 0.8)));
+        }
+
+        if state.robots().count() > 1 {
+            for (i, (id, rb)) in state.robots().enumerate() {
+                let label = if id == selected {
+                    format!("> {} <", rb.name())
+                } else {
+                    rb.name().to_string()
+                };
+                let color = if id == selected { robot_tint(id) } else { robot_tint(id).with_alpha(0.6) };
+                r.draw_text(&label, DrawParams::new()
+                    .position(Vec2::new(10.0 + i as f32 * TAB_WIDTH, TABS_Y))
+                    .scale(Vec2::new(0.8, 0.8))
+                    .color(color));
+            }
+        }
     }
-    pub fn update_rizz(&mut self, new_rizz: String) {
-        self.rizzler_text.set_content(format!("{}", new_rizz));
+    ///draws the energy bar at 'x' with the sparkline of the latest values below it,
+    ///recharges are marked with a tick
+    fn draw_bar(&self, r: &mut dyn Renderer, robot: &RobotState, x: f32) {
+        let ratio = (robot.energy() as f32 / self.max_energy as f32).min(1.0);
+        r.draw_rect(Rectangle::new(x, BAR_Y, BAR_WIDTH, BAR_HEIGHT), Color::rgb(0.2, 0.2, 0.2));
+        r.draw_rect(Rectangle::new(x, BAR_Y, BAR_WIDTH * ratio, BAR_HEIGHT), energy_color(robot.energy(), self.max_energy));
+
+        let history = robot.energy_history();
+        if history.len() < 2 {
+            return;
+        }
+        let bottom = BAR_Y + BAR_HEIGHT + 4.0 + SPARKLINE_HEIGHT;
+        let step = BAR_WIDTH / (history.len() - 1) as f32;
+        let point = |i: usize, energy: usize| Vec2::new(
+            x + i as f32 * step,
+            bottom - (energy as f32 / self.max_energy as f32).min(1.0) * SPARKLINE_HEIGHT,
+        );
+        for (i, (from, to)) in history.iter().zip(history.iter().skip(1)).enumerate() {
+            r.draw_line(point(i, *from), point(i + 1, *to), 1.0, energy_color(*to, self.max_energy));
+            if to > from {
+                r.draw_rect(Rectangle::new(x + (i + 1) as f32 * step, bottom - SPARKLINE_HEIGHT, 1.0, SPARKLINE_HEIGHT),
+                            Color::rgba(0.4, 0.8, 1.0, 0.8));
+            }
+        }
     }
 }
@@ -0,0 +1,37 @@
+use robotics_lib::world::environmental_conditions::WeatherType;
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+
+use crate::visualizer::renderer::Renderer;
+use crate::visualizer::textures::Texturizable;
+use crate::visualizer::visstate::VisState;
+
+const ITEM_WIDTH: f32 = 32.0;
+const ICON_SCALE: f32 = 0.4;
+const STRIP_Y: f32 = 76.0;
+
+///visualizable weather forecast, a strip of weather icons with the hour each one starts
+#[derive(Default)]
+pub struct VisForecast;
+
+impl VisForecast {
+    pub fn new() -> Self {
+        Self
+    }
+    ///draws the forecast of 'state' starting at 'x', as many entries as fit in the window
+    pub fn draw(&self, r: &mut dyn Renderer, state: &VisState, x: f32) {
+        let fitting = ((r.window_size().0 - x) / ITEM_WIDTH).max(0.0) as usize;
+        for (i, (hour, weather)) in state.forecast().iter().take(fitting).enumerate() {
+            let item_x = x + i as f32 * ITEM_WIDTH;
+            //rain and monsoon share the drop, the monsoon one is darker
+            let tint = if *weather == WeatherType::TropicalMonsoon { Color::rgb(0.45, 0.5, 0.7) } else { Color::WHITE };
+            r.draw_sprite(weather.get_sprite(), None, DrawParams::new()
+                .position(Vec2::new(item_x, STRIP_Y))
+                .scale(Vec2::new(ICON_SCALE, ICON_SCALE))
+                .color(tint));
+            r.draw_text(&format!("{:02}h", hour), DrawParams::new()
+                .position(Vec2::new(item_x, STRIP_Y + 26.0))
+                .scale(Vec2::new(0.6, 0.6)));
+        }
+    }
+}
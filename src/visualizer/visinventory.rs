@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+use robotics_lib::world::tile::Content;
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+
+use crate::visualizer::renderer::Renderer;
+use crate::visualizer::textures::content_name;
+use crate::visualizer::visstate::VisState;
+
+///entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 500;
+///entries shown at once
+const VISIBLE_LINES: usize = 12;
+const LINE_HEIGHT: f32 = 16.0;
+const PANEL_WIDTH: f32 = 230.0;
+const PANEL_Y: f32 = 80.0;
+
+///a single backpack change
+#[derive(Clone, Debug, PartialEq)]
+pub struct InventoryEntry {
+    pub step: usize,
+    pub robot: usize,
+    pub content: Content,
+    pub delta: i64,
+}
+
+///scrollable history of the backpack changes of every robot
+pub struct VisInventoryLog {
+    entries: VecDeque<InventoryEntry>,
+    ///entries skipped from the newest one
+    scroll: usize,
+    visible: bool,
+}
+
+impl Default for VisInventoryLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VisInventoryLog {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            scroll: 0,
+            visible: false,
+        }
+    }
+    ///adds the 'deltas' of robot 'robot' happened at 'step'
+    pub fn record(&mut self, step: usize, robot: usize, deltas: &[(Content, i64)]) {
+        for (content, delta) in deltas {
+            self.entries.push_back(InventoryEntry { step, robot, content: content.clone(), delta: *delta });
+            if self.entries.len() > MAX_ENTRIES {
+                self.entries.pop_front();
+            }
+            //keep looking at the same entries while scrolled back
+            if self.scroll > 0 {
+                self.scroll += 1;
+            }
+        }
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+    pub fn entries(&self) -> impl Iterator<Item = &InventoryEntry> {
+        self.entries.iter()
+    }
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    ///scrolls towards older entries, 'lines' can be negative to go back to the newest
+    pub fn scroll(&mut self, lines: i32) {
+        let scroll = self.scroll as i64 + lines as i64;
+        self.scroll = scroll.clamp(0, self.max_scroll() as i64) as usize;
+    }
+    fn max_scroll(&self) -> usize {
+        self.entries.len().saturating_sub(VISIBLE_LINES)
+    }
+    ///draws the newest entries, oldest on top, on the right side of the window
+    pub fn draw(&self, r: &mut dyn Renderer, state: &VisState) {
+        if !self.visible {
+            return;
+        }
+        let x = r.window_size().0 - PANEL_WIDTH;
+        let title = if self.scroll > 0 {
+            format!("Inventory log (-{})", self.scroll)
+        } else {
+            "Inventory log".to_string()
+        };
+        r.draw_text(&title, Vec2::new(x, PANEL_Y).into());
+
+        let end = self.entries.len() - self.scroll;
+        let start = end.saturating_sub(VISIBLE_LINES);
+        for (line, entry) in self.entries.range(start..end).enumerate() {
+            let color = if entry.delta > 0 { Color::rgb(0.4, 1.0, 0.4) } else { Color::rgb(1.0, 0.4, 0.4) };
+            let name = state.robot(entry.robot).map_or("?", |rb| rb.name());
+            r.draw_text(
+                &format!("#{} {} {:+} {}", entry.step, name, entry.delta, content_name(&entry.content)),
+                DrawParams::new()
+                    .position(Vec2::new(x, PANEL_Y + (line + 1) as f32 * LINE_HEIGHT))
+                    .scale(Vec2::new(0.7, 0.7))
+                    .color(color),
+            );
+        }
+    }
+}
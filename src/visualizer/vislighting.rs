@@ -0,0 +1,58 @@
+use tetra::graphics::{Color, Rectangle};
+
+use crate::visualizer::renderer::Renderer;
+
+///tint over the map at given minutes of the day, blended linearly in between
+const KEYFRAMES: [(u16, Color); 8] = [
+    (0, NIGHT),
+    (5 * 60, NIGHT),
+    (6 * 60 + 30, DAWN),
+    (8 * 60, DAY),
+    (17 * 60 + 30, DAY),
+    (19 * 60, DUSK),
+    (20 * 60 + 30, NIGHT),
+    (24 * 60, NIGHT),
+];
+const NIGHT: Color = Color::rgba(0.05, 0.08, 0.3, 0.55);
+const DAWN: Color = Color::rgba(1.0, 0.6, 0.4, 0.25);
+const DAY: Color = Color::rgba(1.0, 1.0, 1.0, 0.0);
+const DUSK: Color = Color::rgba(0.9, 0.4, 0.25, 0.3);
+
+///part of the day, as shown next to the clock
+pub fn day_phase(hour: u8) -> &'static str {
+    match hour {
+        5..=7 => "dawn",
+        8..=17 => "day",
+        18..=20 => "dusk",
+        _ => "night",
+    }
+}
+
+///color laid over the map at 'hour':'minute'
+pub fn daylight_tint(hour: u8, minute: u8) -> Color {
+    let time = (hour as u16 % 24) * 60 + minute as u16 % 60;
+    for pair in KEYFRAMES.windows(2) {
+        let ((from_t, from), (to_t, to)) = (pair[0], pair[1]);
+        if time < to_t {
+            let t = (time - from_t) as f32 / (to_t - from_t) as f32;
+            return Color::rgba(
+                from.r + (to.r - from.r) * t,
+                from.g + (to.g - from.g) * t,
+                from.b + (to.b - from.b) * t,
+                from.a + (to.a - from.a) * t,
+            );
+        }
+    }
+    NIGHT
+}
+
+///lays the daylight tint of 'time_of_day' over the whole window, nothing when the time is unknown
+pub fn draw_lighting(r: &mut dyn Renderer, time_of_day: Option<(u8, u8)>) {
+    if let Some((hour, minute)) = time_of_day {
+        let tint = daylight_tint(hour, minute);
+        if tint.a > 0.0 {
+            let (width, height) = r.window_size();
+            r.draw_rect(Rectangle::new(0.0, 0.0, width, height), tint);
+        }
+    }
+}
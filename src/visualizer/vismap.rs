@@ -1,125 +1,533 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ops::Range;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, thread_rng};
+use rand_chacha::ChaCha8Rng;
 use robotics_lib::world::environmental_conditions::WeatherType;
-use robotics_lib::world::tile::Tile;
-use tetra::{Context, State};
-use tetra::graphics::{DrawParams, Texture};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use tetra::graphics::{Color, DrawParams, Rectangle};
 use tetra::math::Vec2;
 
-use crate::visualizer::{PIXEL, TOP_OFFSET};
-use crate::visualizer::textures::{Drawable, upload_contentset, upload_tileset};
+use crate::visualizer::chunkmap::CHUNK_SIZE;
+use crate::visualizer::renderer::{Image, Renderer, Sprite};
+use crate::visualizer::textures::{content_color, Drawable, iso_rect, robot_tint, tile_color};
+use crate::visualizer::viscamera::{Camera, MAX_ELEVATION, Projection};
+use crate::visualizer::viseffects::VisEffects;
+use crate::visualizer::vismotion::{Facing, ROBOT_MOVE_DURATION, VisMotion};
+use crate::visualizer::visstate::{VisAction, VisState};
 use crate::visualizer::visweather::VisWeather;
-use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+const ROBOT_LABEL_OFFSET: f32 = 14.0;
+const ROBOT_LABEL_SCALE: f32 = 0.7;
+///seconds of snowfall to cover the tiles completely
+const SNOW_BUILDUP: f32 = 30.0;
+///seconds for a full snow cover to melt
+const SNOW_MELT: f32 = 60.0;
+const SNOW_MAX_ALPHA: f32 = 0.6;
+const FOG_COLOR: Color = Color::rgb(0.72, 0.72, 0.76);
+///size of the content dot of flat tiles, relative to the tile
+const CONTENT_DOT: f32 = 0.4;
+///brightness of the sides of raised isometric tiles
+const SIDE_SHADE: f32 = 0.6;
+///smallest size in pixels of the robot markers
+const ROBOT_MARKER: f32 = 4.0;
+const TELEPORT_COLOR: Color = Color::rgb(0.75, 0.45, 1.0);
+///thickness in pixels of the teleport ring
+const TELEPORT_RING: f32 = 2.0;
+///default zoom under which tiles are drawn as flat colors
+pub const LOD_SIMPLE_BELOW: f32 = 0.2;
+///default zoom under which the map is drawn as a single image
+pub const LOD_IMAGE_BELOW: f32 = 0.08;
+///off-screen layer holding the tiles, see [Renderer::begin_layer]
+pub const MAP_LAYER: usize = 0;
+///default length of the cross-fade between two weather types
+pub const WEATHER_TRANSITION: Duration = Duration::from_secs(3);
+
+///how detailed the map is drawn, depending on the zoom
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lod {
+    ///tileset and contentset sprites
+    Sprites,
+    ///a flat color per tile type with a dot for the content
+    Simple,
+    ///the map as images of a pixel per tile, one per chunk, updated only when tiles change
+    Image,
+}
 
 ///visualizable map structure
 pub struct VisMap {
-    discovered_map: Vec<Vec<Option<Tile>>>,
     visweather: VisWeather,
+    ///previous weathers, fading out while the current one fades in
+    fading: Vec<VisWeather>,
+    ///seconds a weather takes to fade in or out
+    transition: f32,
+    ///snow laying on the tiles, from 0 to 1
+    snow_cover: f32,
+    ///seeds the weather effects, one after the other
+    rng: ChaCha8Rng,
+    lod_simple_below: f32,
+    lod_image_below: f32,
+    ///discovered map a pixel per tile for [Lod::Image], by first row and column of the chunk,
+    ///created the first time a chunk is drawn that way
+    images: HashMap<(usize, usize), Image>,
+    layer: MapLayer,
+    ///robots walking between tiles
+    motion: VisMotion,
+    ///effects of the robots' actions on the tiles
+    effects: VisEffects,
+}
 
-    world_size: usize,
-    pub robot_position: (usize, usize),
+///how the tiles are drawn, besides the camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TileOptions {
+    style: usize,
+    show_discoverers: bool,
+    lod: Lod,
 }
 
-impl VisMap {
-    ///creates a new viusalizable [size x size] map of 'None' contents
-    pub fn new(size: usize) -> Self {
-        let new_mappa = vec![vec![None; size]; size];
+///everything the content of [MAP_LAYER] depends on
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct LayerKey {
+    camera: Camera,
+    window_size: (f32, f32),
+    options: TileOptions,
+}
+
+///bookkeeping of the tiles drawn in [MAP_LAYER]
+#[derive(Default)]
+struct MapLayer {
+    ///what the layer was last drawn with, None if it never was
+    drawn_with: Option<LayerKey>,
+    ///tiles changed since the layer was last drawn
+    dirty: Vec<(usize, usize)>,
+}
 
+impl VisMap {
+    ///creates the map with randomly seeded weather effects
+    pub fn new(weather_type: WeatherType) -> Self {
+        Self::with_seed(weather_type, thread_rng().gen())
+    }
+    ///creates the map with weather effects seeded by 'seed', to render replays identically
+    pub fn with_seed(weather_type: WeatherType, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
         Self {
-            visweather: VisWeather::new(WeatherType::Rainy),
-            discovered_map: new_mappa,
-            robot_position: (1, 1),
-            world_size: size,
-        }
-    }
-    ///draws the map
-    pub fn new_draw(&mut self, ctx: &mut Context, map_pos: (f32, f32), scale: f32, style : usize) {
-        self.visweather.update(ctx);
-        let mut y_pixel = map_pos.1;
-        let mut x_pixel = map_pos.0;
-
-        let tileset = upload_tileset(ctx, style);
-        let contentset = upload_contentset(ctx, style);
-
-        for (xrobot, row) in self.discovered_map.iter().enumerate() {
-            for (yrobot, opt_tile) in row.iter().enumerate() {
-                if opt_tile.is_some()
-                    && x_pixel < WINDOW_WIDTH as f32
-                    && y_pixel < WINDOW_HEIGHT as f32
-                    && x_pixel >= 0.0
-                    && y_pixel > 0.0
-                {
-                    let tile = opt_tile.clone().unwrap();
-                    tile.tile_type.draw(tileset.clone(), ctx,
-                                        DrawParams::new()
-                                            .position(Vec2::new(x_pixel, TOP_OFFSET + y_pixel))
-                                            .scale(Vec2::new(scale, scale)));
-                    tile.content.draw(contentset.clone(), ctx,
-                                      DrawParams::new()
-                                          .position(Vec2::new(x_pixel, TOP_OFFSET + y_pixel))
-                                          .scale(Vec2::new(scale, scale)));
-                    if yrobot == self.robot_position.1 && xrobot == self.robot_position.0 {
-                        Texture::new(ctx, "./resources/robot.png")
-                            .expect("failed to upload robot image")
-                            .draw(ctx,
-                                  DrawParams::new()
-                                      .position(Vec2::new(x_pixel, TOP_OFFSET + y_pixel))
-                                      .scale(Vec2::new(scale, scale)),
-                            );
+            visweather: VisWeather::new(weather_type, rng.gen()),
+            fading: Vec::new(),
+            transition: WEATHER_TRANSITION.as_secs_f32(),
+            snow_cover: 0.0,
+            rng,
+            lod_simple_below: LOD_SIMPLE_BELOW,
+            lod_image_below: LOD_IMAGE_BELOW,
+            images: HashMap::new(),
+            layer: MapLayer::default(),
+            motion: VisMotion::new(ROBOT_MOVE_DURATION),
+            effects: VisEffects::new(),
+        }
+    }
+    ///sets how long the cross-fade between two weather types lasts, zero switches instantly
+    pub fn set_weather_transition(&mut self, duration: Duration) {
+        self.transition = duration.as_secs_f32();
+    }
+    ///sets how long robots take to walk to the next tile, zero jumps straight to it
+    pub fn set_robot_move_duration(&mut self, duration: Duration) {
+        self.motion.set_duration(duration);
+    }
+    ///animates robot 'robot' moving from 'from' to 'to', see [VisMotion::robot_moved]
+    pub fn robot_moved(&mut self, robot: usize, from: (usize, usize), to: (usize, usize)) {
+        self.motion.robot_moved(robot, from, to);
+    }
+    pub fn motion(&self) -> &VisMotion {
+        &self.motion
+    }
+    ///shows the effect of 'action' on 'tile', which held 'content' before
+    pub fn action(&mut self, action: VisAction, tile: (usize, usize), content: &Content) {
+        self.effects.spawn(&mut self.rng, action, tile, content);
+    }
+    pub fn effects(&self) -> &VisEffects {
+        &self.effects
+    }
+    ///tiles changed since the map layer was last drawn
+    pub fn pending_tiles(&self) -> &[(usize, usize)] {
+        &self.layer.dirty
+    }
+    ///sets the strength of the current weather, from 0 to 1
+    pub fn set_weather_intensity(&mut self, intensity: f32) {
+        self.visweather.set_intensity(intensity);
+    }
+    pub fn weather(&self) -> &VisWeather {
+        &self.visweather
+    }
+    ///weathers fading out, oldest first
+    pub fn fading_weathers(&self) -> &[VisWeather] {
+        &self.fading
+    }
+    ///advances the robot moves, the action and weather effects and the snow cover by 'dt' seconds
+    pub fn update(&mut self, dt: f32, camera: &Camera, window_size: (f32, f32)) {
+        self.motion.update(dt);
+        self.effects.update(dt, &mut self.rng);
+        for weather in self.fading.iter_mut() {
+            weather.update(dt, camera, window_size);
+        }
+        self.fading.retain(|w| !w.is_gone());
+        self.visweather.update(dt, camera, window_size);
+        self.snow_cover = if self.visweather.is_snowing() {
+            (self.snow_cover + dt / SNOW_BUILDUP).min(1.0)
+        } else {
+            (self.snow_cover - dt / SNOW_MELT).max(0.0)
+        };
+    }
+    ///level of detail used at 'scale'
+    pub fn lod(&self, scale: f32) -> Lod {
+        if scale < self.lod_image_below {
+            Lod::Image
+        } else if scale < self.lod_simple_below {
+            Lod::Simple
+        } else {
+            Lod::Sprites
+        }
+    }
+    ///sets the zooms under which tiles are drawn as flat colors and the map as a single image
+    pub fn set_lod_scales(&mut self, simple_below: f32, image_below: f32) {
+        self.lod_simple_below = simple_below;
+        self.lod_image_below = image_below;
+    }
+    ///marks the tiles at 'changed' to be redrawn in the map layer, if there is one,
+    ///and writes them into the map images already created
+    pub fn tiles_changed(&mut self, state: &VisState, changed: &[(usize, usize)]) {
+        //without a layer the next one is drawn whole anyway
+        if self.layer.drawn_with.is_some() {
+            self.layer.dirty.extend_from_slice(changed);
+        }
+        if self.images.is_empty() {
+            return;
+        }
+        for &(row, col) in changed {
+            let origin = (row / CHUNK_SIZE * CHUNK_SIZE, col / CHUNK_SIZE * CHUNK_SIZE);
+            if let (Some(image), Some(tile)) = (self.images.get_mut(&origin), state.tile(row, col)) {
+                //rows grow to the right, as on screen
+                image.set_pixel(row - origin.0, col - origin.1, tile_color(tile.tile_type));
+            }
+        }
+    }
+    ///draws the discovered map of 'state', the robots, the action effects and the weather on top
+    ///
+    ///with 'show_discoverers' tiles are tinted with the color of the robot that discovered them.
+    ///when zoomed out, see [Lod], tiles become flat colors and then the map images.
+    ///renderers keeping layers get the tiles drawn once in [MAP_LAYER], and then only
+    ///the ones changed since, until the camera or the drawing options change
+    pub fn draw(&mut self, r: &mut dyn Renderer, state: &VisState, camera: &Camera, style: usize, show_discoverers: bool) {
+        let (width, height) = r.window_size();
+        let tile_size = camera.tile_size();
+        let scale = camera.scale;
+        let iso = camera.projection == Projection::Isometric;
+        //the map image is top-down only
+        let lod = match self.lod(scale) {
+            Lod::Image if iso => Lod::Simple,
+            lod => lod,
+        };
+        //tiles even partially inside the window
+        let (rows, cols) = camera.visible_tiles((width, height), state.world_size());
+        let visible = |(row, col): (usize, usize)| rows.contains(&row) && cols.contains(&col);
+        let options = TileOptions { style, show_discoverers, lod };
+
+        if lod == Lod::Image {
+            self.layer = MapLayer::default();
+            self.draw_map_images(r, state, camera, rows.clone(), cols.clone());
+        } else if r.keeps_layers() {
+            let drawn_with = Some(LayerKey { camera: *camera, window_size: (width, height), options });
+            //isometric tiles overlap, a changed one must be drawn behind the ones in front
+            if self.layer.drawn_with != drawn_with || (iso && !self.layer.dirty.is_empty()) {
+                r.begin_layer(MAP_LAYER, true);
+                for (coords, tile) in ordered_tiles(state, camera, rows.clone(), cols.clone()) {
+                    draw_tile(r, state, camera, coords, tile, options);
+                }
+                r.end_layer();
+            } else if !self.layer.dirty.is_empty() {
+                r.begin_layer(MAP_LAYER, false);
+                for &coords in &self.layer.dirty {
+                    if let (true, Some(tile)) = (visible(coords), state.tile(coords.0, coords.1)) {
+                        draw_tile(r, state, camera, coords, tile, options);
                     }
                 }
+                r.end_layer();
+            }
+            self.layer.drawn_with = drawn_with;
+            self.layer.dirty.clear();
+            r.draw_layer(MAP_LAYER);
+        } else {
+            self.layer = MapLayer::default();
+            for (coords, tile) in ordered_tiles(state, camera, rows.clone(), cols.clone()) {
+                draw_tile(r, state, camera, coords, tile, options);
+            }
+        }
 
-                y_pixel += PIXEL * scale;
+        //snow and fog change every frame, so they stay out of the layer
+        let foggy = self.weathers().any(|w| w.droptype == WeatherType::Foggy);
+        if lod != Lod::Image && (self.snow_cover > 0.0 || foggy) {
+            for (coords, tile) in ordered_tiles(state, camera, rows.clone(), cols.clone()) {
+                if self.snow_cover > 0.0 && holds_snow(tile.tile_type) {
+                    draw_tile_shape(r, camera, coords, tile.elevation, Color::WHITE.with_alpha(self.snow_cover * SNOW_MAX_ALPHA));
+                }
+                if foggy {
+                    let distance = distance_to_robots(state, coords);
+                    let fog = self.weathers().map(|w| w.fog_alpha(distance)).fold(0.0, f32::max);
+                    if fog > 0.0 {
+                        draw_tile_shape(r, camera, coords, tile.elevation, FOG_COLOR.with_alpha(fog));
+                    }
+                }
             }
-            y_pixel = map_pos.1;
-            x_pixel += PIXEL * scale;
-        }
-        self.visweather.draw(ctx);
-    }
-    ///updates visulizer's memory inserting 'view' vec
-    pub(crate) fn update_map(&mut self, view: Vec<Vec<Option<Tile>>>) {
-        let mut valid_cells = vec![vec![true; 3]; 3];
-        let c_row = self.robot_position.0;
-        let c_col = self.robot_position.1;
-        if c_row == 0 {
-            valid_cells[0][0] = false;
-            valid_cells[0][1] = false;
-            valid_cells[0][2] = false;
-        }
-        if c_col == 0 {
-            valid_cells[0][0] = false;
-            valid_cells[1][0] = false;
-            valid_cells[2][0] = false;
-        }
-        if c_row == self.world_size - 1 {
-            valid_cells[2][0] = false;
-            valid_cells[2][1] = false;
-            valid_cells[2][2] = false;
-        }
-        if c_col == self.world_size - 1 {
-            valid_cells[0][2] = false;
-            valid_cells[1][2] = false;
-            valid_cells[2][2] = false;
-        }
-        for (i, row) in valid_cells.iter().enumerate() {
-            for (j, is_valid) in row.iter().enumerate() {
-                if *is_valid {
-                    //if let None = self.discovered_map[c_row + i - 1][c_col + j - 1] {
-                        let tile = view[i][j].clone().unwrap();
-                        self.discovered_map[c_row + i - 1][c_col + j - 1] = Some(tile);
-                   // }
+        }
+
+        let labels = state.robots().count() > 1;
+        for (id, robot) in state.robots() {
+            let (row, col) = robot.position();
+            let pose = self.motion.pose(id, (row, col), camera.projection);
+            if let (true, Some((x_pixel, y_pixel))) = (visible((row, col)), self.robot_screen_pos(state, id, camera)) {
+                if let Some(progress) = pose.teleport {
+                    let feet = if iso { tile_size } else { tile_size / 2.0 };
+                    draw_teleport_ring(r, camera, Vec2::new(x_pixel + tile_size / 2.0, y_pixel + feet), progress);
+                }
+                let tint = robot_tint(id).with_alpha(pose.alpha);
+                if lod == Lod::Image {
+                    //the sprite would be a pixel or two, a marker stays visible
+                    let size = tile_size.max(ROBOT_MARKER);
+                    let offset = (tile_size - size) / 2.0;
+                    r.draw_rect(Rectangle::new(x_pixel + offset, y_pixel + offset, size, size), tint);
+                } else {
+                    //flipped around its middle when looking left
+                    let (x_sprite, x_scale) = match pose.facing {
+                        Facing::Right => (x_pixel, scale),
+                        Facing::Left => (x_pixel + tile_size, -scale),
+                    };
+                    r.draw_sprite(Sprite::Robot, None, DrawParams::new()
+                        .position(Vec2::new(x_sprite, y_pixel))
+                        .scale(Vec2::new(x_scale, scale))
+                        .color(tint));
+                }
+                if labels {
+                    r.draw_text(robot.name(), DrawParams::new()
+                        .position(Vec2::new(x_pixel, y_pixel - ROBOT_LABEL_OFFSET))
+                        .scale(Vec2::new(ROBOT_LABEL_SCALE, ROBOT_LABEL_SCALE))
+                        .color(robot_tint(id)));
                 }
             }
         }
+        if lod != Lod::Image {
+            self.effects.draw(r, state, camera);
+        }
+        for weather in self.weathers() {
+            weather.draw(r, camera);
+        }
     }
-    ///updates the robot (texture pointer) position on the map
-    pub fn update_robot_pos(&mut self, new_pos: (usize, usize)) {
-        self.robot_position = new_pos;
+    ///top left corner of the sprite of robot 'robot' of 'state', where it is drawn now
+    ///
+    ///None if the robot is unknown or stands on a tile not discovered yet
+    pub fn robot_screen_pos(&self, state: &VisState, robot: usize, camera: &Camera) -> Option<(f32, f32)> {
+        let position = state.robot(robot)?.position();
+        let tile = state.tile(position.0, position.1)?;
+        let pose = self.motion.pose(robot, position, camera.projection);
+        let (x_pixel, y_pixel) = camera.tile_pos_to_screen(pose.position);
+        if camera.projection == Projection::TopDown {
+            return Some((x_pixel, y_pixel));
+        }
+        //standing in the middle of the diamond, as high as the closest tile
+        let (near_row, near_col) = (pose.position.0.round() as usize, pose.position.1.round() as usize);
+        let elevation = state.tile(near_row, near_col).unwrap_or(tile).elevation;
+        Some((x_pixel, y_pixel - camera.tile_size() / 2.0 - camera.elevation_lift(elevation)))
     }
-
-    ///updates the weather
+    ///discovered tile of 'state' under the screen point 'point', if any
+    ///
+    ///isometric tiles are lifted by their elevation and cover the ones behind,
+    ///so the tiles further down the diagonal are tried first
+    pub fn pick(&self, state: &VisState, camera: &Camera, point: Vec2<f32>) -> Option<(usize, usize)> {
+        let tile_at = |point: Vec2<f32>| {
+            let (row, col) = camera.screen_to_tile(point);
+            (row >= 0.0 && col >= 0.0).then_some((row as usize, col as usize))
+        };
+        if camera.projection == Projection::TopDown {
+            return tile_at(point).filter(|(row, col)| state.tile(*row, *col).is_some());
+        }
+        let (row, col) = tile_at(point)?;
+        let reach = (camera.elevation_lift(MAX_ELEVATION) / camera.tile_size()).ceil() as usize + 1;
+        for step in (0..=reach).rev() {
+            for candidate in [(row + step + 1, col + step), (row + step, col + step + 1), (row + step, col + step)] {
+                if let Some(tile) = state.tile(candidate.0, candidate.1) {
+                    let lift = camera.elevation_lift(tile.elevation);
+                    if tile_at(Vec2::new(point.x, point.y + lift)) == Some(candidate) {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+    ///cross-fades to new weather particles when the weather type changes,
+    ///the old ones stop spawning and fade out while the new ones ramp up
     pub fn update_weather(&mut self, weather_type: WeatherType) {
         if weather_type != self.visweather.droptype {
-            println!("weather updated from {:?} to {:?}", self.visweather, weather_type);
-            self.visweather = VisWeather::new(weather_type);
+            let mut weather = VisWeather::new(weather_type, self.rng.gen());
+            weather.set_intensity(self.visweather.intensity());
+            weather.fade_in(self.transition);
+            let mut old = std::mem::replace(&mut self.visweather, weather);
+            old.fade_out(self.transition);
+            if !old.is_gone() {
+                self.fading.push(old);
+            }
         }
     }
-}
\ No newline at end of file
+    ///the fading weathers and the current one, in drawing order
+    fn weathers(&self) -> impl Iterator<Item = &VisWeather> {
+        self.fading.iter().chain(std::iter::once(&self.visweather))
+    }
+    ///draws the images of the chunks overlapping 'rows' and 'cols',
+    ///creating the ones of discovered chunks drawn for the first time
+    fn draw_map_images(&mut self, r: &mut dyn Renderer, state: &VisState, camera: &Camera,
+                       rows: Range<usize>, cols: Range<usize>) {
+        let size = state.world_size();
+        for first_row in (rows.start / CHUNK_SIZE * CHUNK_SIZE..rows.end).step_by(CHUNK_SIZE) {
+            for first_col in (cols.start / CHUNK_SIZE * CHUNK_SIZE..cols.end).step_by(CHUNK_SIZE) {
+                let chunk_rows = first_row..(first_row + CHUNK_SIZE).min(size);
+                let chunk_cols = first_col..(first_col + CHUNK_SIZE).min(size);
+                let image = match self.images.entry((first_row, first_col)) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        if state.tiles_in(chunk_rows.clone(), chunk_cols.clone()).next().is_none() {
+                            continue;
+                        }
+                        entry.insert(chunk_image(state, chunk_rows.clone(), chunk_cols.clone()))
+                    }
+                };
+                let visible_rows = rows.start.max(first_row)..rows.end.min(chunk_rows.end);
+                let visible_cols = cols.start.max(first_col)..cols.end.min(chunk_cols.end);
+                draw_map_image(r, image, (first_row, first_col), camera, visible_rows, visible_cols);
+            }
+        }
+    }
+}
+
+///discovered tiles in 'rows' and 'cols' in drawing order, isometric ones from back to front
+fn ordered_tiles<'a>(state: &'a VisState, camera: &Camera, rows: Range<usize>, cols: Range<usize>) -> Vec<((usize, usize), &'a Tile)> {
+    let mut tiles: Vec<((usize, usize), &Tile)> = state.tiles_in(rows, cols).collect();
+    if camera.projection == Projection::Isometric {
+        tiles.sort_by_key(|((row, col), _)| (row + col, *row));
+    }
+    tiles
+}
+
+///draws tile [row][col] of 'state', sprites or flat colors depending on the [Lod]
+fn draw_tile(r: &mut dyn Renderer, state: &VisState, camera: &Camera, coords: (usize, usize), tile: &Tile, options: TileOptions) {
+    let (x_pixel, y_pixel) = camera.tile_to_screen(coords);
+    let tile_size = camera.tile_size();
+    let tint = match state.discovered_by(coords.0, coords.1) {
+        Some(id) if options.show_discoverers => robot_tint(id),
+        _ => Color::WHITE,
+    };
+    let lift = camera.elevation_lift(tile.elevation);
+    if lift > 0.0 {
+        //the sides of the raised tile, a darker copy at ground level joined to the top
+        let side = shade(tile_color(tile.tile_type) * tint, SIDE_SHADE);
+        draw_tile_shape(r, camera, coords, 0, side);
+        r.draw_rect(Rectangle::new(x_pixel - tile_size / 2.0, y_pixel + tile_size / 2.0 - lift, tile_size * 2.0, lift), side);
+    }
+    match (options.lod, camera.projection) {
+        (Lod::Sprites, Projection::TopDown) => {
+            let params = DrawParams::new()
+                .position(Vec2::new(x_pixel, y_pixel))
+                .scale(Vec2::new(camera.scale, camera.scale));
+            r.draw_sprite(Sprite::Tileset(options.style), Some(tile.tile_type.atlas_rect()), params.clone().color(tint));
+            r.draw_sprite(Sprite::Contentset(options.style), Some(tile.content.atlas_rect()), params);
+        }
+        (Lod::Sprites, Projection::Isometric) => {
+            let top = y_pixel - lift;
+            r.draw_sprite(Sprite::IsoTileset(options.style), Some(iso_rect(tile.tile_type.atlas_rect())), DrawParams::new()
+                .position(Vec2::new(x_pixel - tile_size / 2.0, top))
+                .scale(Vec2::new(camera.scale, camera.scale))
+                .color(tint));
+            //contents stand upright on the middle of the diamond
+            r.draw_sprite(Sprite::Contentset(options.style), Some(tile.content.atlas_rect()), DrawParams::new()
+                .position(Vec2::new(x_pixel, top + tile_size / 2.0 - tile_size))
+                .scale(Vec2::new(camera.scale, camera.scale)));
+        }
+        _ => {
+            draw_tile_shape(r, camera, coords, tile.elevation, tile_color(tile.tile_type) * tint);
+            if tile.content != Content::None {
+                let dot = (tile_size * CONTENT_DOT).max(1.0);
+                let offset = (tile_size - dot) / 2.0;
+                r.draw_rect(Rectangle::new(x_pixel + offset, y_pixel + offset - lift, dot, dot), content_color(&tile.content));
+            }
+        }
+    }
+}
+
+///fills tile [row][col], lifted by 'elevation', with 'color': a square or a diamond
+fn draw_tile_shape(r: &mut dyn Renderer, camera: &Camera, coords: (usize, usize), elevation: usize, color: Color) {
+    let (x_pixel, y_pixel) = camera.tile_to_screen(coords);
+    let tile_size = camera.tile_size();
+    match camera.projection {
+        Projection::TopDown => r.draw_rect(Rectangle::new(x_pixel, y_pixel, tile_size, tile_size), color),
+        Projection::Isometric => r.draw_sprite(Sprite::Diamond, None, DrawParams::new()
+            .position(Vec2::new(x_pixel - tile_size / 2.0, y_pixel - camera.elevation_lift(elevation)))
+            .scale(Vec2::new(camera.scale, camera.scale))
+            .color(color)),
+    }
+}
+
+///ring around a teleporting robot standing at 'center': it shrinks onto the tile
+///left while the robot fades out, and grows from the one reached while it fades in
+fn draw_teleport_ring(r: &mut dyn Renderer, camera: &Camera, center: Vec2<f32>, progress: f32) {
+    //0 halfway through, 1 at both ends
+    let distance = (progress - 0.5).abs() * 2.0;
+    let half = camera.tile_size() * (0.25 + distance);
+    let color = TELEPORT_COLOR.with_alpha(1.0 - distance);
+    //a square, or a diamond lying on the isometric tiles
+    let corners = match camera.projection {
+        Projection::TopDown => [(-half, -half), (half, -half), (half, half), (-half, half)],
+        Projection::Isometric => [(0.0, -half / 2.0), (half, 0.0), (0.0, half / 2.0), (-half, 0.0)],
+    }.map(|(dx, dy)| Vec2::new(center.x + dx, center.y + dy));
+    for i in 0..corners.len() {
+        r.draw_line(corners[i], corners[(i + 1) % corners.len()], TELEPORT_RING, color);
+    }
+}
+
+///'color' darkened by 'factor', keeping its alpha
+fn shade(color: Color, factor: f32) -> Color {
+    Color::rgba(color.r * factor, color.g * factor, color.b * factor, color.a)
+}
+
+///image of the chunk covering 'rows' and 'cols', a pixel per discovered tile
+fn chunk_image(state: &VisState, rows: Range<usize>, cols: Range<usize>) -> Image {
+    let mut image = Image::new(rows.len(), cols.len());
+    for ((row, col), tile) in state.tiles_in(rows.clone(), cols.clone()) {
+        image.set_pixel(row - rows.start, col - cols.start, tile_color(tile.tile_type));
+    }
+    image
+}
+
+///draws the 'rows' and 'cols' of the chunk 'image' starting at 'origin', each pixel stretched over a tile
+fn draw_map_image(r: &mut dyn Renderer, image: &Image, origin: (usize, usize), camera: &Camera,
+                  rows: Range<usize>, cols: Range<usize>) {
+    let (x_pixel, y_pixel) = camera.tile_to_screen((rows.start, cols.start));
+    let region = Rectangle::new((rows.start - origin.0) as f32, (cols.start - origin.1) as f32,
+                                rows.len() as f32, cols.len() as f32);
+    r.draw_image(image, Some(region), DrawParams::new()
+        .position(Vec2::new(x_pixel, y_pixel))
+        .scale(Vec2::new(camera.tile_size(), camera.tile_size())));
+}
+
+///whether snow settles on tiles of this type
+fn holds_snow(tile_type: TileType) -> bool {
+    !matches!(tile_type, TileType::DeepWater | TileType::ShallowWater | TileType::Lava | TileType::Snow)
+}
+
+///distance in tiles from [row][col] to the closest robot
+fn distance_to_robots(state: &VisState, (row, col): (usize, usize)) -> f32 {
+    state.robots()
+        .map(|(_, robot)| {
+            let (r, c) = robot.position();
+            let (dr, dc) = (r as f32 - row as f32, c as f32 - col as f32);
+            (dr * dr + dc * dc).sqrt()
+        })
+        .fold(f32::INFINITY, f32::min)
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::visualizer::viscamera::Projection;
+
+///default time a robot takes to walk from a tile to the next one
+pub const ROBOT_MOVE_DURATION: Duration = Duration::from_millis(250);
+
+///side a robot sprite looks at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Facing {
+    #[default]
+    Right,
+    Left,
+}
+
+///how a robot is drawn at a given moment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RobotPose {
+    ///tile coordinates, with the fraction while walking
+    pub position: (f32, f32),
+    pub facing: Facing,
+    ///opacity, robots fade out and back in while teleporting
+    pub alpha: f32,
+    ///progress of the teleport from 0 to 1, None when not teleporting
+    pub teleport: Option<f32>,
+}
+
+///a move being animated
+#[derive(Clone, Copy, Debug)]
+struct Move {
+    ///where the sprite was when the move started, maybe between two tiles
+    from: (f32, f32),
+    to: (usize, usize),
+    ///seconds since the move started
+    elapsed: f32,
+    ///jumps further than a tile are not walked
+    teleport: bool,
+}
+
+///last horizontal direction of a robot on screen, in both projections
+#[derive(Clone, Copy, Debug, Default)]
+struct Facings {
+    top_down: Facing,
+    isometric: Facing,
+}
+
+///robots walking smoothly from a tile to the next instead of jumping,
+///facing the direction they move to
+pub struct VisMotion {
+    duration: f32,
+    moves: HashMap<usize, Move>,
+    facings: HashMap<usize, Facings>,
+}
+
+impl VisMotion {
+    ///robots take 'duration' for each move, zero jumps straight to the new tile
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration: duration.as_secs_f32(),
+            moves: HashMap::new(),
+            facings: HashMap::new(),
+        }
+    }
+    ///robots take 'duration' for the next moves, zero also stops the ones running
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration.as_secs_f32();
+        if self.duration <= 0.0 {
+            self.moves.clear();
+        }
+    }
+    ///starts moving robot 'robot' from 'from' to 'to'
+    ///
+    ///a robot still walking starts from where it is drawn, so consecutive moves chain smoothly.
+    ///jumps to a tile that is not next to 'from' are teleports
+    pub fn robot_moved(&mut self, robot: usize, from: (usize, usize), to: (usize, usize)) {
+        let (rows, cols) = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+        let facings = self.facings.entry(robot).or_default();
+        //rows grow to the right in both projections, columns only move sideways in isometric
+        facings.top_down = facing(rows, facings.top_down);
+        facings.isometric = facing(rows - cols, facings.isometric);
+
+        if self.duration <= 0.0 || from == to {
+            self.moves.remove(&robot);
+            return;
+        }
+        let teleport = rows.abs() > 1 || cols.abs() > 1;
+        let start = match self.moves.get(&robot) {
+            Some(current) if !teleport && !current.teleport && current.to == from => self.walked(current),
+            _ => (from.0 as f32, from.1 as f32),
+        };
+        self.moves.insert(robot, Move { from: start, to, elapsed: 0.0, teleport });
+    }
+    ///advances the moves by 'dt' seconds, dropping the finished ones
+    pub fn update(&mut self, dt: f32) {
+        let duration = self.duration;
+        for current in self.moves.values_mut() {
+            current.elapsed += dt;
+        }
+        self.moves.retain(|_, current| current.elapsed < duration);
+    }
+    ///whether some robot is still moving
+    pub fn is_moving(&self) -> bool {
+        !self.moves.is_empty()
+    }
+    ///how robot 'robot', standing on 'position', is drawn now in 'projection'
+    ///
+    ///a move towards another tile than 'position' is stale, the robot is drawn on 'position'
+    pub fn pose(&self, robot: usize, position: (usize, usize), projection: Projection) -> RobotPose {
+        let facings = self.facings.get(&robot).copied().unwrap_or_default();
+        let facing = match projection {
+            Projection::TopDown => facings.top_down,
+            Projection::Isometric => facings.isometric,
+        };
+        let standing = RobotPose {
+            position: (position.0 as f32, position.1 as f32),
+            facing,
+            alpha: 1.0,
+            teleport: None,
+        };
+        match self.moves.get(&robot) {
+            Some(current) if current.to == position => {
+                if current.teleport {
+                    //fades out on the old tile, then in on the new one
+                    let t = self.progress(current);
+                    let (at, alpha) = if t < 0.5 { (current.from, 1.0 - 2.0 * t) } else { (standing.position, 2.0 * t - 1.0) };
+                    RobotPose { position: at, alpha, teleport: Some(t), ..standing }
+                } else {
+                    RobotPose { position: self.walked(current), ..standing }
+                }
+            }
+            _ => standing,
+        }
+    }
+    ///where a walking robot is drawn, easing in and out of the tiles
+    fn walked(&self, current: &Move) -> (f32, f32) {
+        let t = self.progress(current);
+        let t = t * t * (3.0 - 2.0 * t);
+        let (to_row, to_col) = (current.to.0 as f32, current.to.1 as f32);
+        (current.from.0 + (to_row - current.from.0) * t, current.from.1 + (to_col - current.from.1) * t)
+    }
+    fn progress(&self, current: &Move) -> f32 {
+        (current.elapsed / self.duration).clamp(0.0, 1.0)
+    }
+}
+
+///side a robot looks at after moving 'dx' on screen, vertical moves keep the previous one
+fn facing(dx: i64, previous: Facing) -> Facing {
+    match dx {
+        dx if dx < 0 => Facing::Left,
+        dx if dx > 0 => Facing::Right,
+        _ => previous,
+    }
+}
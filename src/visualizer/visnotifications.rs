@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::config::VisConfig;
+use crate::visualizer::renderer::Renderer;
+use crate::visualizer::visconsole::{Severity, TEXT_SCALE};
+use crate::visualizer::visstate::{VisEvent, VisState};
+
+const TOAST_DURATION: f32 = 3.0;
+///part of the life spent fading out
+const FADE: f32 = 0.3;
+const MAX_TOASTS: usize = 5;
+const TOAST_WIDTH: f32 = 280.0;
+const TOAST_HEIGHT: f32 = 22.0;
+const TOASTS_Y: f32 = 70.0;
+
+///conditions that raise a notification, each can be turned off in [VisConfig]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    ///energy went below [VisConfig::low_energy_threshold]
+    LowEnergy,
+    WeatherChanged,
+    BackpackFull,
+    ///robot on the same tile for [VisConfig::stuck_steps] steps of the world
+    Stuck,
+    ///the channel of the updates has been closed
+    Disconnected,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 5] = [
+        NotificationKind::LowEnergy,
+        NotificationKind::WeatherChanged,
+        NotificationKind::BackpackFull,
+        NotificationKind::Stuck,
+        NotificationKind::Disconnected,
+    ];
+    pub fn severity(self) -> Severity {
+        match self {
+            NotificationKind::WeatherChanged => Severity::Info,
+            NotificationKind::LowEnergy | NotificationKind::BackpackFull | NotificationKind::Stuck => Severity::Warning,
+            NotificationKind::Disconnected => Severity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    ///robot the notification is about, None when it is about the whole world
+    pub robot: Option<usize>,
+    pub text: String,
+}
+
+///derives notifications from the events of the updates
+///
+///every condition is notified when it starts, not again while it lasts
+pub struct Notifier {
+    enabled: HashSet<NotificationKind>,
+    low_energy_threshold: usize,
+    stuck_steps: usize,
+    ///last energy seen per robot, a robot not seen yet is not above the threshold either
+    energy: HashMap<usize, usize>,
+    ///consecutive steps without moving and the time of day of the last one, per robot
+    still: HashMap<usize, (usize, Option<(u8, u8)>)>,
+    full: HashSet<usize>,
+    disconnected: bool,
+}
+
+impl Notifier {
+    pub fn new(config: &VisConfig) -> Self {
+        Self {
+            enabled: config.notifications.clone(),
+            low_energy_threshold: config.low_energy_threshold,
+            stuck_steps: config.stuck_steps,
+            energy: HashMap::new(),
+            still: HashMap::new(),
+            full: HashSet::new(),
+            disconnected: false,
+        }
+    }
+    ///notifications raised by an update of robot 'robot', that produced 'events' on 'state'
+    pub fn observe(&mut self, robot: usize, events: &[VisEvent], state: &VisState) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        let name = state.robot(robot).map_or("?", |rb| rb.name());
+
+        if let Some(energy) = state.robot(robot).map(|rb| rb.energy()) {
+            let previous = self.energy.insert(robot, energy);
+            if energy < self.low_energy_threshold && previous.is_none_or(|e| e >= self.low_energy_threshold) {
+                notifications.push(Notification {
+                    kind: NotificationKind::LowEnergy,
+                    robot: Some(robot),
+                    text: format!("{} is low on energy ({})", name, energy),
+                });
+            }
+        }
+
+        for event in events {
+            match event {
+                VisEvent::WeatherChanged { from, to } => {
+                    notifications.push(Notification {
+                        kind: NotificationKind::WeatherChanged,
+                        robot: None,
+                        text: format!("Weather changed from {:?} to {:?}", from, to),
+                    });
+                }
+                VisEvent::BackpackChanged { .. } => {
+                    let full = state.robot(robot).is_some_and(|rb| rb.backpack_used() >= rb.backpack_size());
+                    if full && self.full.insert(robot) {
+                        notifications.push(Notification {
+                            kind: NotificationKind::BackpackFull,
+                            robot: Some(robot),
+                            text: format!("{}'s backpack is full", name),
+                        });
+                    } else if !full {
+                        self.full.remove(&robot);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        //a step is an update advancing the time of day, or any update when the time is not sent,
+        //so the several updates a robot may send in the same step are counted once
+        let moved = events.iter().any(|e| matches!(e, VisEvent::RobotMoved { robot: r, .. } if *r == robot));
+        let time = state.time_of_day();
+        let (steps, last_time) = self.still.entry(robot).or_insert((0, None));
+        let stepped = time.is_none() || time != *last_time;
+        *last_time = time;
+        if moved {
+            *steps = 0;
+        } else if stepped {
+            *steps += 1;
+            if *steps == self.stuck_steps {
+                notifications.push(Notification {
+                    kind: NotificationKind::Stuck,
+                    robot: Some(robot),
+                    text: format!("{} has not moved for {} steps", name, self.stuck_steps),
+                });
+            }
+        }
+
+        notifications.retain(|n| self.enabled.contains(&n.kind));
+        notifications
+    }
+    ///notification for the closed channel, only the first time
+    pub fn disconnected(&mut self) -> Option<Notification> {
+        if self.disconnected || !self.enabled.contains(&NotificationKind::Disconnected) {
+            return None;
+        }
+        self.disconnected = true;
+        Some(Notification {
+            kind: NotificationKind::Disconnected,
+            robot: None,
+            text: "Robot channel disconnected".to_string(),
+        })
+    }
+}
+
+struct Toast {
+    text: String,
+    severity: Severity,
+    ///seconds since the toast appeared
+    age: f32,
+}
+
+///transient notifications stacked at the top of the window, newest first
+#[derive(Default)]
+pub struct VisToasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl VisToasts {
+    pub fn new() -> Self {
+        Self { toasts: VecDeque::new() }
+    }
+    pub fn push(&mut self, notification: &Notification) {
+        self.toasts.push_front(Toast {
+            text: notification.text.clone(),
+            severity: notification.kind.severity(),
+            age: 0.0,
+        });
+        self.toasts.truncate(MAX_TOASTS);
+    }
+    ///advances the toasts by 'dt' seconds, dropping the expired ones
+    pub fn update(&mut self, dt: f32) {
+        for toast in self.toasts.iter_mut() {
+            toast.age += dt;
+        }
+        self.toasts.retain(|t| t.age < TOAST_DURATION);
+    }
+    pub fn draw(&self, r: &mut dyn Renderer) {
+        let x = (r.window_size().0 - TOAST_WIDTH) / 2.0;
+        for (i, toast) in self.toasts.iter().enumerate() {
+            let progress = toast.age / TOAST_DURATION;
+            let alpha = if progress < 1.0 - FADE { 1.0 } else { ((1.0 - progress) / FADE).max(0.0) };
+            let y = TOASTS_Y + i as f32 * (TOAST_HEIGHT + 4.0);
+            r.draw_rect(Rectangle::new(x, y, TOAST_WIDTH, TOAST_HEIGHT), Color::rgba(0.1, 0.1, 0.1, 0.8 * alpha));
+            r.draw_rect(Rectangle::new(x, y, 4.0, TOAST_HEIGHT), toast.severity.color().with_alpha(alpha));
+            r.draw_text(&toast.text, DrawParams::new()
+                .position(Vec2::new(x + 10.0, y + 3.0))
+                .scale(Vec2::new(TEXT_SCALE, TEXT_SCALE))
+                .color(Color::WHITE.with_alpha(alpha)));
+        }
+    }
+}
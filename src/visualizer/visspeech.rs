@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tetra::graphics::{Color, DrawParams, Rectangle};
+use tetra::math::Vec2;
+
+use crate::visualizer::renderer::Renderer;
+use crate::visualizer::viscamera::Camera;
+use crate::visualizer::visconsole::{wrap, CHAR_WIDTH, TEXT_SCALE};
+use crate::visualizer::vismap::VisMap;
+use crate::visualizer::visstate::VisState;
+
+const MAX_CHARS: usize = 28;
+const LINE_HEIGHT: f32 = 16.0;
+const PADDING: f32 = 4.0;
+///room between the bubble and the robot
+const GAP: f32 = 8.0;
+///part of the life spent fading out
+const FADE: f32 = 0.3;
+
+struct Bubble {
+    lines: Vec<String>,
+    ///seconds since the message arrived
+    age: f32,
+}
+
+///speech bubbles with the latest message of each robot, drawn above it in map space
+pub struct VisSpeech {
+    bubbles: BTreeMap<usize, Bubble>,
+    duration: f32,
+}
+
+impl VisSpeech {
+    ///bubbles stay on screen for 'duration', fading at the end
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            bubbles: BTreeMap::new(),
+            duration: duration.as_secs_f32().max(f32::EPSILON),
+        }
+    }
+    ///shows 'text' above robot 'robot', replacing its previous bubble
+    pub fn say(&mut self, robot: usize, text: &str) {
+        self.bubbles.insert(robot, Bubble { lines: wrap(text, MAX_CHARS), age: 0.0 });
+    }
+    ///advances the bubbles by 'dt' seconds, dropping the expired ones
+    pub fn update(&mut self, dt: f32) {
+        for bubble in self.bubbles.values_mut() {
+            bubble.age += dt;
+        }
+        let duration = self.duration;
+        self.bubbles.retain(|_, b| b.age < duration);
+    }
+    ///draws the bubbles above the robots of 'state', following them as 'map' draws them
+    pub fn draw(&self, r: &mut dyn Renderer, state: &VisState, map: &VisMap, camera: &Camera) {
+        let labels = state.robots().count() > 1;
+        for (id, bubble) in &self.bubbles {
+            let (x_pixel, y_pixel) = match map.robot_screen_pos(state, *id, camera) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let longest = bubble.lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            let width = longest as f32 * CHAR_WIDTH + 2.0 * PADDING;
+            let height = bubble.lines.len() as f32 * LINE_HEIGHT + 2.0 * PADDING;
+            let x = x_pixel + camera.tile_size() / 2.0 - width / 2.0;
+            //above the name label, when there is one
+            let bottom = y_pixel - GAP - if labels { LINE_HEIGHT } else { 0.0 };
+            let y = bottom - height;
+
+            let progress = bubble.age / self.duration;
+            let alpha = if progress < 1.0 - FADE { 1.0 } else { ((1.0 - progress) / FADE).max(0.0) };
+            let background = Color::rgba(1.0, 1.0, 1.0, 0.9 * alpha);
+            r.draw_rect(Rectangle::new(x, y, width, height), background);
+            r.draw_rect(Rectangle::new(x_pixel + camera.tile_size() / 2.0 - 3.0, bottom, 6.0, GAP / 2.0), background);
+            for (i, line) in bubble.lines.iter().enumerate() {
+                r.draw_text(line, DrawParams::new()
+                    .position(Vec2::new(x + PADDING, y + PADDING + i as f32 * LINE_HEIGHT))
+                    .scale(Vec2::new(TEXT_SCALE, TEXT_SCALE))
+                    .color(Color::rgba(0.0, 0.0, 0.0, alpha)));
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::btree_map::Entry;
+use std::ops::Range;
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::{Content, Tile};
+use serde::{Deserialize, Serialize};
+
+use crate::visualizer::VisData;
+use crate::visualizer::chunkmap::ChunkMap;
+use crate::visualizer::contents::content_order;
+
+///backpack capacity assumed until a robot sends its own
+pub const DEFAULT_BACKPACK_SIZE: usize = 16;
+///energy values remembered per robot
+pub const ENERGY_HISTORY_LEN: usize = 120;
+
+///what changed while applying a [VisData]
+#[derive(Clone, Debug, PartialEq)]
+pub enum VisEvent {
+    ///first update received from a robot
+    RobotAdded(usize),
+    EnergyChanged { robot: usize, from: usize, to: usize },
+    RobotMoved { robot: usize, from: (usize, usize), to: (usize, usize) },
+    ///coordinates of the tiles written by the update
+    TilesChanged(Vec<(usize, usize)>),
+    ///quantity differences per content kind, sorted like the backpack slots
+    BackpackChanged { robot: usize, deltas: Vec<(Content, i64)> },
+    WeatherChanged { from: WeatherType, to: WeatherType },
+    ///new strength of the weather, from 0 to 1
+    WeatherIntensityChanged(f32),
+    Message { robot: usize, text: String, severity: Severity },
+    ///robot 'robot' did 'action' on 'tile', which held 'content' before
+    Action { robot: usize, action: VisAction, tile: (usize, usize), content: Content },
+}
+
+///importance of a message, picks its color in the console
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+///interaction of a robot with a tile, shown as a short effect on the map
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VisAction {
+    ///a Tree or a Rock destroyed
+    Destroy,
+    ///a Coin collected
+    Collect,
+    ///something put into a Bin or a Bank
+    Put,
+    ///a Fire extinguished
+    Extinguish,
+}
+
+impl VisAction {
+    ///action that turned the content of a tile from 'from' into 'to', if it is one of the above
+    pub fn detect(from: &Content, to: &Content) -> Option<Self> {
+        match (from, to) {
+            (Content::Tree(a), Content::Tree(b)) | (Content::Rock(a), Content::Rock(b)) if b < a => Some(VisAction::Destroy),
+            (Content::Tree(_) | Content::Rock(_), Content::None) => Some(VisAction::Destroy),
+            (Content::Coin(a), Content::Coin(b)) if b < a => Some(VisAction::Collect),
+            (Content::Coin(_), Content::None) => Some(VisAction::Collect),
+            (Content::Bin(a), Content::Bin(b)) | (Content::Bank(a), Content::Bank(b)) if b.start > a.start => Some(VisAction::Put),
+            (Content::Fire, Content::None) => Some(VisAction::Extinguish),
+            _ => None,
+        }
+    }
+}
+
+///data of a single robot
+pub struct RobotState {
+    name: String,
+    position: (usize, usize),
+    energy: usize,
+    ///latest energy values, oldest first
+    energy_history: VecDeque<usize>,
+    backpack: HashMap<Content, usize>,
+    backpack_size: usize,
+    ///backpack contents at each step a backpack was received
+    backpack_history: Vec<(usize, Vec<(Content, usize)>)>,
+    rizz_text: Option<String>,
+}
+
+impl RobotState {
+    fn new(id: usize) -> Self {
+        Self {
+            name: format!("Robot {}", id),
+            position: (1, 1),
+            energy: 0,
+            energy_history: VecDeque::new(),
+            backpack: HashMap::new(),
+            backpack_size: DEFAULT_BACKPACK_SIZE,
+            backpack_history: Vec::new(),
+            rizz_text: None,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+    pub fn energy(&self) -> usize {
+        self.energy
+    }
+    ///last [ENERGY_HISTORY_LEN] energy values received, oldest first
+    pub fn energy_history(&self) -> &VecDeque<usize> {
+        &self.energy_history
+    }
+    pub fn backpack(&self) -> &HashMap<Content, usize> {
+        &self.backpack
+    }
+    ///capacity of the backpack, in items
+    pub fn backpack_size(&self) -> usize {
+        self.backpack_size
+    }
+    ///non empty backpack entries, always in the same order of content kind
+    pub fn backpack_items(&self) -> Vec<(&Content, usize)> {
+        let mut items: Vec<(&Content, usize)> = self.backpack.iter()
+            .filter(|(_, q)| **q > 0)
+            .map(|(c, q)| (c, *q))
+            .collect();
+        items.sort_by_key(|(c, _)| content_order(c));
+        items
+    }
+    ///backpack contents over time, as (step, non empty entries) pairs
+    pub fn backpack_history(&self) -> &[(usize, Vec<(Content, usize)>)] {
+        &self.backpack_history
+    }
+    ///number of items in the backpack
+    pub fn backpack_used(&self) -> usize {
+        self.backpack.values().sum()
+    }
+    ///latest text sent by the AI, if any
+    pub fn rizz_text(&self) -> Option<&str> {
+        self.rizz_text.as_deref()
+    }
+}
+
+///a discovered tile with the robot that saw it first
+struct DiscoveredTile {
+    tile: Tile,
+    discovered_by: usize,
+}
+
+///visualization model, independent of any renderer
+///
+///the discovered map and the weather are shared, everything else is per robot
+pub struct VisState {
+    ///discovered tiles, only the chunks around them are allocated
+    discovered_map: ChunkMap<DiscoveredTile>,
+    world_size: usize,
+
+    robots: BTreeMap<usize, RobotState>,
+    weather: WeatherType,
+    ///strength of the weather, from 0 to 1
+    weather_intensity: f32,
+    ///hour and minute, once a robot sends them
+    time_of_day: Option<(u8, u8)>,
+    ///upcoming weather as (starting hour, weather)
+    forecast: Vec<(u8, WeatherType)>,
+    ///number of updates applied so far
+    step: usize,
+}
+
+impl VisState {
+    ///creates a new [size x size] state with nothing discovered yet
+    pub fn new(size: usize) -> Self {
+        Self {
+            discovered_map: ChunkMap::new(size),
+            world_size: size,
+            robots: BTreeMap::new(),
+            weather: WeatherType::Rainy,
+            weather_intensity: 1.0,
+            time_of_day: None,
+            forecast: Vec::new(),
+            step: 0,
+        }
+    }
+    ///applies every field of 'data' to the robot it comes from and reports what changed
+    pub fn apply(&mut self, data: VisData) -> Vec<VisEvent> {
+        let mut events = Vec::new();
+        let id = data.recv_robot_id;
+        self.step += 1;
+
+        if let Entry::Vacant(e) = self.robots.entry(id) {
+            e.insert(RobotState::new(id));
+            events.push(VisEvent::RobotAdded(id));
+        }
+        let robot = self.robots.get_mut(&id).unwrap();
+        if let Some(name) = data.recv_robot_name {
+            robot.name = name;
+        }
+        if data.recv_energy != robot.energy {
+            events.push(VisEvent::EnergyChanged { robot: id, from: robot.energy, to: data.recv_energy });
+            robot.energy = data.recv_energy;
+        }
+        robot.energy_history.push_back(data.recv_energy);
+        if robot.energy_history.len() > ENERGY_HISTORY_LEN {
+            robot.energy_history.pop_front();
+        }
+        let from = robot.position;
+        self.update_robot_pos(id, data.recv_coordinates);
+        let to = self.robots[&id].position;
+        if from != to {
+            events.push(VisEvent::RobotMoved { robot: id, from, to });
+        }
+        //what was around the robot, to tell what it did to it
+        let around = self.contents_around(to);
+        if let Some(view) = data.recv_discovered_tiles {
+            let changed = self.update_map(id, view);
+            if !changed.is_empty() {
+                events.push(VisEvent::TilesChanged(changed));
+            }
+        }
+        events.extend(self.actions(id, around, data.recv_action));
+        let robot = self.robots.get_mut(&id).unwrap();
+        if let Some(size) = data.recv_backpack_size {
+            robot.backpack_size = size;
+        }
+        if let Some(backp) = data.recv_backpack {
+            let deltas = backpack_deltas(&robot.backpack, &backp);
+            robot.backpack = backp;
+            let items = robot.backpack_items().into_iter().map(|(c, q)| (c.clone(), q)).collect();
+            robot.backpack_history.push((self.step, items));
+            events.push(VisEvent::BackpackChanged { robot: id, deltas });
+        }
+        if let Some(s) = data.recv_rizz_text {
+            events.push(VisEvent::Message { robot: id, text: s.clone(), severity: data.recv_severity });
+            robot.rizz_text = Some(s);
+        }
+        if let Some(w) = data.recv_weather {
+            if w != self.weather {
+                events.push(VisEvent::WeatherChanged { from: self.weather, to: w });
+                self.weather = w;
+            }
+        }
+        if let Some(intensity) = data.recv_weather_intensity {
+            let intensity = intensity.clamp(0.0, 1.0);
+            if intensity != self.weather_intensity {
+                events.push(VisEvent::WeatherIntensityChanged(intensity));
+                self.weather_intensity = intensity;
+            }
+        }
+        if let Some(forecast) = data.recv_forecast {
+            self.forecast = forecast;
+        }
+        if let Some((hour, minute)) = data.recv_time_of_day {
+            self.time_of_day = Some((hour % 24, minute % 60));
+        }
+        events
+    }
+    ///inserts the 'view' of robot 'id', centered on it, into the discovered map
+    ///
+    ///cells outside the world or missing from the view are skipped,
+    ///returns the coordinates that have been written
+    pub fn update_map(&mut self, id: usize, view: Vec<Vec<Option<Tile>>>) -> Vec<(usize, usize)> {
+        let mut changed = Vec::new();
+        let center = match self.robots.get(&id) {
+            Some(robot) => robot.position,
+            None => return changed,
+        };
+        let radius = (view.len() / 2) as i64;
+        let (c_row, c_col) = (center.0 as i64, center.1 as i64);
+
+        for (i, view_row) in view.into_iter().enumerate() {
+            for (j, opt_tile) in view_row.into_iter().enumerate() {
+                let row = c_row + i as i64 - radius;
+                let col = c_col + j as i64 - radius;
+                if row < 0 || col < 0 || row >= self.world_size as i64 || col >= self.world_size as i64 {
+                    continue;
+                }
+                if let Some(tile) = opt_tile {
+                    let (row, col) = (row as usize, col as usize);
+                    match self.discovered_map.get_mut(row, col) {
+                        Some(discovered) => discovered.tile = tile,
+                        None => { self.discovered_map.insert(row, col, DiscoveredTile { tile, discovered_by: id }); }
+                    }
+                    changed.push((row, col));
+                }
+            }
+        }
+        changed
+    }
+    ///contents of the discovered tiles around 'center', itself included
+    fn contents_around(&self, center: (usize, usize)) -> Vec<((usize, usize), Content)> {
+        let rows = center.0.saturating_sub(1)..center.0 + 2;
+        let cols = center.1.saturating_sub(1)..center.1 + 2;
+        self.tiles_in(rows, cols).map(|(coords, tile)| (coords, tile.content.clone())).collect()
+    }
+    ///actions of robot 'id': the 'explicit' one, if sent, and the ones
+    ///detected by comparing the contents it had 'around' with the current ones
+    fn actions(&self, id: usize, around: Vec<((usize, usize), Content)>, explicit: Option<(VisAction, (usize, usize))>) -> Vec<VisEvent> {
+        let mut events = Vec::new();
+        if let Some((action, tile)) = explicit {
+            let content = around.iter()
+                .find(|(coords, _)| *coords == tile)
+                .map(|(_, content)| content.clone())
+                .or_else(|| self.tile(tile.0, tile.1).map(|t| t.content.clone()))
+                .unwrap_or(Content::None);
+            events.push(VisEvent::Action { robot: id, action, tile, content });
+        }
+        for (coords, before) in around {
+            if matches!(explicit, Some((_, tile)) if tile == coords) {
+                continue;
+            }
+            if let Some(action) = self.tile(coords.0, coords.1).and_then(|t| VisAction::detect(&before, &t.content)) {
+                events.push(VisEvent::Action { robot: id, action, tile: coords, content: before });
+            }
+        }
+        events
+    }
+    ///updates the position of robot 'id', ignoring coordinates outside the world
+    pub fn update_robot_pos(&mut self, id: usize, new_pos: (usize, usize)) {
+        if new_pos.0 < self.world_size && new_pos.1 < self.world_size {
+            if let Some(robot) = self.robots.get_mut(&id) {
+                robot.position = new_pos;
+            }
+        }
+    }
+
+    pub fn world_size(&self) -> usize {
+        self.world_size
+    }
+    ///returns the discovered tile at [row][col], if any
+    pub fn tile(&self, row: usize, col: usize) -> Option<&Tile> {
+        self.discovered_map.get(row, col).map(|d| &d.tile)
+    }
+    ///returns the id of the robot that first discovered [row][col]
+    pub fn discovered_by(&self, row: usize, col: usize) -> Option<usize> {
+        self.discovered_map.get(row, col).map(|d| d.discovered_by)
+    }
+    ///iterates over the discovered tiles with their coordinates, chunk by chunk
+    pub fn discovered_tiles(&self) -> impl Iterator<Item = ((usize, usize), &Tile)> {
+        self.discovered_map.iter().map(|(coords, d)| (coords, &d.tile))
+    }
+    ///iterates over the discovered tiles in 'rows' and 'cols', chunk by chunk,
+    ///without looking at the rest of the map
+    pub fn tiles_in(&self, rows: Range<usize>, cols: Range<usize>) -> impl Iterator<Item = ((usize, usize), &Tile)> {
+        self.discovered_map.iter_in(rows, cols).map(|(coords, d)| (coords, &d.tile))
+    }
+    ///rows and columns of the map chunks holding discovered tiles
+    pub fn discovered_chunks(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+        self.discovered_map.chunks()
+    }
+    pub fn robot(&self, id: usize) -> Option<&RobotState> {
+        self.robots.get(&id)
+    }
+    ///iterates over the robots ordered by id
+    pub fn robots(&self) -> impl Iterator<Item = (usize, &RobotState)> {
+        self.robots.iter().map(|(id, r)| (*id, r))
+    }
+    pub fn robot_ids(&self) -> Vec<usize> {
+        self.robots.keys().copied().collect()
+    }
+    pub fn weather(&self) -> WeatherType {
+        self.weather
+    }
+    ///strength of the weather, from 0 to 1
+    pub fn weather_intensity(&self) -> f32 {
+        self.weather_intensity
+    }
+    pub fn forecast(&self) -> &[(u8, WeatherType)] {
+        &self.forecast
+    }
+    pub fn time_of_day(&self) -> Option<(u8, u8)> {
+        self.time_of_day
+    }
+    ///number of updates applied so far, used as step counter
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+
+///differences between two backpacks, skipping unchanged kinds
+fn backpack_deltas(old: &HashMap<Content, usize>, new: &HashMap<Content, usize>) -> Vec<(Content, i64)> {
+    let mut deltas: Vec<(Content, i64)> = new.iter()
+        .map(|(c, q)| (c.clone(), *q as i64 - *old.get(c).unwrap_or(&0) as i64))
+        .chain(old.iter()
+            .filter(|(c, _)| !new.contains_key(*c))
+            .map(|(c, q)| (c.clone(), -(*q as i64))))
+        .filter(|(_, d)| *d != 0)
+        .collect();
+    deltas.sort_by_key(|(c, _)| content_order(c));
+    deltas
+}
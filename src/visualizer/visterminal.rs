@@ -0,0 +1,194 @@
+use std::io::{self, Stdout, Write};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use crossterm::{cursor, event, queue, terminal};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use robotics_lib::world::tile::Content;
+
+use crate::visualizer::{DEFAULT_ROBOT, VisData};
+use crate::visualizer::contents::{content_name, robot_tint, Rgb, tile_color};
+use crate::visualizer::visstate::VisState;
+
+const POLL_TIME: Duration = Duration::from_millis(50);
+///lines reserved at the top for energy, backpack and rizz text
+const HUD_LINES: u16 = 3;
+
+///terminal (ANSI) visualizer, draws the discovered map around the robot
+pub struct VisTerminal {
+    state: VisState,
+
+    receiver: Receiver<VisData>,
+    offset: (i32, i32),
+    ///robot followed by the view and shown in the hud
+    selected_robot: usize,
+}
+
+impl VisTerminal {
+    pub fn new(size: usize, receiver: Receiver<VisData>) -> Self {
+        Self {
+            state: VisState::new(size),
+            receiver,
+            offset: (0, 0),
+            selected_robot: DEFAULT_ROBOT,
+        }
+    }
+    ///runs the terminal loop until 'q' or 'Esc' is pressed
+    pub fn run(&mut self) -> io::Result<()> {
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        queue!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        let res = self.main_loop(&mut out);
+
+        queue!(out, ResetColor, cursor::Show, terminal::LeaveAlternateScreen)?;
+        out.flush()?;
+        terminal::disable_raw_mode()?;
+        res
+    }
+    fn main_loop(&mut self, out: &mut Stdout) -> io::Result<()> {
+        let mut redraw = true;
+        loop {
+            while let Ok(data) = self.receiver.try_recv() {
+                if self.state.robot(self.selected_robot).is_none() {
+                    self.selected_robot = data.recv_robot_id;
+                }
+                self.state.apply(data);
+                redraw = true;
+            }
+            if event::poll(POLL_TIME)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        match key.code {
+                            //map movement
+                            KeyCode::Char('a') | KeyCode::Left => { self.offset.0 -= 1; }
+                            KeyCode::Char('d') | KeyCode::Right => { self.offset.0 += 1; }
+                            KeyCode::Char('w') | KeyCode::Up => { self.offset.1 -= 1; }
+                            KeyCode::Char('s') | KeyCode::Down => { self.offset.1 += 1; }
+                            //back on the robot
+                            KeyCode::Char('c') => { self.offset = (0, 0); }
+                            KeyCode::Tab => { self.select_next_robot(); }
+                            KeyCode::Char('q') | KeyCode::Esc => { return Ok(()); }
+                            _ => {}
+                        }
+                        redraw = true;
+                    }
+                    Event::Resize(_, _) => { redraw = true; }
+                    _ => {}
+                }
+            }
+            if redraw {
+                self.draw(out)?;
+                redraw = false;
+            }
+        }
+    }
+    fn select_next_robot(&mut self) {
+        let ids = self.state.robot_ids();
+        let next = ids.iter().position(|id| *id == self.selected_robot).map_or(0, |i| i + 1);
+        if let Some(id) = ids.get(next % ids.len().max(1)) {
+            self.selected_robot = *id;
+            self.offset = (0, 0);
+        }
+    }
+    fn draw(&self, out: &mut Stdout) -> io::Result<()> {
+        let (cols, rows) = terminal::size()?;
+        queue!(out, ResetColor, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+
+        //hud
+        let selected = self.state.robot(self.selected_robot);
+        let name = selected.map_or("Robot", |r| r.name());
+        queue!(out, Print(format!("{} Energy : {}   Weather : {:?}", name,
+                                  selected.map_or(0, |r| r.energy()), self.state.weather())))?;
+        queue!(out, cursor::MoveTo(0, 1), Print(format!("Backpack : {}", self.backpack_summary())))?;
+        queue!(out, cursor::MoveTo(0, 2), Print(selected.and_then(|r| r.rizz_text()).unwrap_or("...")))?;
+
+        //map, every cell is two columns wide to look squared
+        let width = (cols / 2) as i32;
+        let height = rows.saturating_sub(HUD_LINES) as i32;
+        let robot = selected.map_or((0, 0), |r| r.position());
+        let first_x = robot.0 as i32 + self.offset.0 - width / 2;
+        let first_y = robot.1 as i32 + self.offset.1 - height / 2;
+
+        for sy in 0..height {
+            queue!(out, cursor::MoveTo(0, HUD_LINES + sy as u16))?;
+            for sx in 0..width {
+                let (x, y) = (first_x + sx, first_y + sy);
+                let cell = if x >= 0 && y >= 0 { map_cell(&self.state, (x as usize, y as usize)) } else { None };
+                match cell {
+                    Some(cell) => {
+                        queue!(out,
+                            SetBackgroundColor(cell.background),
+                            SetForegroundColor(cell.foreground),
+                            Print(cell.glyph))?;
+                    }
+                    None => {
+                        queue!(out, ResetColor, Print("  "))?;
+                    }
+                }
+            }
+            queue!(out, ResetColor)?;
+        }
+        out.flush()
+    }
+    fn backpack_summary(&self) -> String {
+        let robot = match self.state.robot(self.selected_robot) {
+            Some(r) => r,
+            None => return "empty".to_string(),
+        };
+        let items: Vec<String> = robot.backpack_items().iter()
+            .map(|(cont, quantity)| format!("{} x{}", content_name(cont), quantity))
+            .collect();
+        let used = format!("[{}/{}]", robot.backpack_used(), robot.backpack_size());
+        if items.is_empty() {
+            format!("{} empty", used)
+        } else {
+            format!("{} {}", used, items.join(", "))
+        }
+    }
+}
+
+///what a map cell shows, two columns wide
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermCell {
+    pub glyph: String,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+///cell of the discovered tile [x][y] of 'state': the robot standing on it
+///or its content, on the color of the tile type. None if not discovered
+pub fn map_cell(state: &VisState, (x, y): (usize, usize)) -> Option<TermCell> {
+    let tile = state.tile(x, y)?;
+    let background = term_color(tile_color(tile.tile_type));
+    Some(match state.robots().find(|(_, r)| r.position() == (x, y)) {
+        Some((id, _)) => TermCell { glyph: format!("@{}", id % 10), foreground: term_color(robot_tint(id)), background },
+        None => TermCell { glyph: content_glyph(&tile.content).to_string(), foreground: Color::White, background },
+    })
+}
+
+///the same colors as the window, as terminal rgb
+fn term_color(color: Rgb) -> Color {
+    let [r, g, b] = color.bytes();
+    Color::Rgb { r, g, b }
+}
+
+fn content_glyph(content: &Content) -> &'static str {
+    match content {
+        Content::Rock(_) => "▲ ",
+        Content::Tree(_) => "♣ ",
+        Content::Garbage(_) => "% ",
+        Content::Fire => "* ",
+        Content::Coin(_) => "$ ",
+        Content::Bin(_) => "u ",
+        Content::Crate(_) => "# ",
+        Content::Bank(_) => "B ",
+        Content::Market(_) => "M ",
+        Content::Fish(_) => "~ ",
+        Content::Building => "H ",
+        Content::Bush(_) => "\" ",
+        Content::Scarecrow => "Y ",
+        _ => "  ",
+    }
+}
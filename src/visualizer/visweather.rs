@@ -1,61 +1,109 @@
 use std::fmt::{Debug, Formatter};
 
-use rand::{self, Rng, thread_rng};
-use rand::rngs::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use robotics_lib::world::environmental_conditions::WeatherType;
 use robotics_lib::world::environmental_conditions::WeatherType::*;
-use tetra::{Context, State};
-use tetra::graphics::DrawParams;
+use tetra::graphics::{Color, DrawParams, Rectangle};
 use tetra::math::Vec2;
 
-use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
-use crate::visualizer::textures::Texturizable;
-
-const INITIAL_DROPS: usize = 100;
-const GRAVITY: f32 = 2.5;
-const CLOUDS: usize = 10;
-
-pub struct Drop {
-    drop_type: WeatherType,
-    position: Vec2<f32>,
-    velocity: Vec2<f32>,
-}
-
-impl Drop {
-    pub(crate) fn new(rng: &mut ThreadRng, drop_type: WeatherType) -> Drop {
-        let velocity;
-        let mut position = Vec2::new(rng.gen::<f32>() * WINDOW_WIDTH as f32, rng.gen::<f32>() * WINDOW_WIDTH as f32);
-        match drop_type {
-            Rainy => {
-                velocity = Vec2::new(6.4, 8.7);
-            }
-            TrentinoSnow => {
-                velocity = Vec2::new(4.3, 6.2);
-            }
-            TropicalMonsoon => {
-                velocity = Vec2::new(8.4, 10.7);
-            }
-            Sunny => {
-                position = Vec2::new(WINDOW_WIDTH as f32 - 60.0, 20.0);
-                velocity = Vec2::new(0.0, 0.0);
-            }
-            _ => {
-                velocity = Vec2::new(0.4, 0.0);
-            }
-        }
-
-        Drop {
-            drop_type,
-            position,
-            velocity,
-        }
+use crate::visualizer::particles::{Emitter, ParticleSystem, Space, Spawn};
+use crate::visualizer::renderer::{Renderer, Sprite, WeatherSprite};
+use crate::visualizer::TOP_OFFSET;
+use crate::visualizer::viscamera::Camera;
+
+///seconds simulated by each step, the same whatever the frame rate
+pub const TICK: f32 = 1.0 / 60.0;
+///longest frame simulated, longer pauses are skipped instead of caught up
+const MAX_FRAME: f32 = 0.25;
+///tolerance on the elapsed time, so rounding doesn't skip a step
+const EPSILON: f32 = 1e-5;
+///tiles around the robot left clear by the fog
+const FOG_CLEAR: f32 = 2.0;
+///tiles over which the fog goes from clear to its thickest
+const FOG_RANGE: f32 = 6.0;
+const FOG_MAX_ALPHA: f32 = 0.85;
+const SHIMMER_BANDS: usize = 6;
+///seconds between lightning flashes, at least and at most
+const LIGHTNING_INTERVAL: (f32, f32) = (3.0, 8.0);
+///alpha lost by a flash per second
+const FLASH_DECAY: f32 = 4.0;
+
+///particle emitters of each weather type
+pub fn emitters(weather: WeatherType) -> Vec<Emitter> {
+    let drop = |rate: f32, velocity: Vec2<f32>, scale: f32| Emitter {
+        sprite: Sprite::Weather(WeatherSprite::Drop),
+        space: Space::Map,
+        spawn: Spawn::Top,
+        rate,
+        max: (rate * 2.0) as usize,
+        velocity,
+        jitter: Vec2::new(40.0, 80.0),
+        lifetime: 2.0,
+        scale: Vec2::new(scale, scale),
+        color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+    };
+    match weather {
+        Rainy => vec![drop(150.0, Vec2::new(380.0, 1050.0), 0.1)],
+        TropicalMonsoon => vec![
+            drop(350.0, Vec2::new(650.0, 1500.0), 0.12),
+            drop(120.0, Vec2::new(500.0, 1300.0), 0.08),
+        ],
+        TrentinoSnow => vec![Emitter {
+            sprite: Sprite::Weather(WeatherSprite::Snow),
+            space: Space::Map,
+            spawn: Spawn::Top,
+            rate: 60.0,
+            max: 500,
+            velocity: Vec2::new(60.0, 180.0),
+            jitter: Vec2::new(50.0, 30.0),
+            lifetime: 8.0,
+            scale: Vec2::new(0.12, 0.12),
+            color: Color::WHITE,
+        }],
+        Foggy => vec![Emitter {
+            sprite: Sprite::Weather(WeatherSprite::Fog),
+            space: Space::Map,
+            spawn: Spawn::Anywhere,
+            rate: 2.0,
+            max: 40,
+            velocity: Vec2::new(40.0, 0.0),
+            jitter: Vec2::new(20.0, 8.0),
+            lifetime: 12.0,
+            scale: Vec2::new(2.5, 2.5),
+            color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+        }],
+        _ => Vec::new(),
     }
 }
 
+///weather effects drawn over the map
+///
+///precipitation and clouds are particles living in map space, sunny weather
+///has a fixed sun and heat shimmer, the monsoon flashes with lightning.
+///the simulation advances in fixed steps with its own seeded generator,
+///so the same seed and the same elapsed time always give the same frame.
+///the intensity scales how strong the effects are, while the fade is moved
+///towards 0 or 1 to cross-fade between weather types
 pub struct VisWeather {
     pub droptype: WeatherType,
-    rng: ThreadRng,
-    drops: Vec<Drop>,
+    rng: ChaCha8Rng,
+    ///strength of the weather, from 0 to 1
+    intensity: f32,
+    ///how much of the weather is shown, from 0 to 1
+    fade: f32,
+    ///fade change per second, negative while fading out
+    fade_speed: f32,
+    systems: Vec<ParticleSystem>,
+    ///elapsed seconds not simulated yet, less than a [TICK]
+    pending: f32,
+    prewarmed: bool,
+    ///seconds since the weather started
+    time: f32,
+    ///alpha of the current lightning flash
+    flash: f32,
+    ///seconds to the next lightning flash
+    next_flash: f32,
 }
 
 impl Debug for VisWeather {
@@ -65,81 +113,137 @@ impl Debug for VisWeather {
 }
 
 impl VisWeather {
-    pub(crate) fn new(droptype: WeatherType) -> VisWeather {
-        let mut rng = thread_rng();
-
-        let mut drop_numbers = INITIAL_DROPS;
-        match droptype {
-            TropicalMonsoon => {
-                drop_numbers = INITIAL_DROPS * 2;
-            }
-            Sunny => {
-                drop_numbers = 1;
-            }
-            Foggy => {
-                drop_numbers = CLOUDS;
-            }
-            _ => {}
-        }
-
-        let mut drops = Vec::with_capacity(drop_numbers);
-        for _ in 0..drop_numbers {
-            drops.push(Drop::new(&mut rng, droptype));
-        }
-
+    pub fn new(droptype: WeatherType, seed: u64) -> VisWeather {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let next_flash = rng.gen_range(LIGHTNING_INTERVAL.0..LIGHTNING_INTERVAL.1);
         VisWeather {
             droptype,
             rng,
-            drops,
+            intensity: 1.0,
+            fade: 1.0,
+            fade_speed: 0.0,
+            systems: emitters(droptype).into_iter().map(ParticleSystem::new).collect(),
+            pending: 0.0,
+            prewarmed: false,
+            time: 0.0,
+            flash: 0.0,
+            next_flash,
         }
     }
-}
-
-impl State for VisWeather {
-    fn update(&mut self, ctx : &mut Context) -> tetra::Result {
-        for drop in &mut self.drops {
-            drop.position += drop.velocity;
-
-            if drop.position.x > WINDOW_WIDTH as f32 {
-                drop.position.x = 0.0;
-            } else if drop.position.x < 0.0 {
-                drop.position.x = WINDOW_WIDTH as f32;
+    pub fn systems(&self) -> &[ParticleSystem] {
+        &self.systems
+    }
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+    ///sets the strength of the weather, from 0 (barely there) to 1
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+    ///starts from nothing and fades in over 'duration' seconds
+    pub fn fade_in(&mut self, duration: f32) {
+        if duration > 0.0 {
+            self.fade = 0.0;
+            self.fade_speed = 1.0 / duration;
+        } else {
+            self.fade = 1.0;
+            self.fade_speed = 0.0;
+        }
+    }
+    ///stops spawning gradually and fades out over 'duration' seconds
+    pub fn fade_out(&mut self, duration: f32) {
+        if duration > 0.0 {
+            self.fade_speed = -1.0 / duration;
+        } else {
+            self.fade = 0.0;
+            self.fade_speed = 0.0;
+        }
+    }
+    ///whether the weather faded out completely and can be dropped
+    pub fn is_gone(&self) -> bool {
+        self.fade <= 0.0 && self.fade_speed <= 0.0
+    }
+    ///how strongly the effects are shown, intensity and fade together
+    fn strength(&self) -> f32 {
+        self.intensity * self.fade
+    }
+    ///advances the weather by 'dt' seconds, as many [TICK]s as fit in it
+    pub fn update(&mut self, dt: f32, camera: &Camera, window_size: (f32, f32)) {
+        self.pending += dt.min(MAX_FRAME);
+        while self.pending >= TICK - EPSILON {
+            self.pending -= TICK;
+            self.step(camera, window_size);
+        }
+    }
+    ///advances the weather by a single [TICK], the particles spawn on the part of the map
+    ///shown by 'camera'
+    pub fn step(&mut self, camera: &Camera, window_size: (f32, f32)) {
+        let dt = TICK;
+        self.time += dt;
+        self.fade = (self.fade + self.fade_speed * dt).clamp(0.0, 1.0);
+        let strength = self.strength();
+        let map_area = camera.visible_map_area(window_size);
+        let screen_area = Rectangle::new(0.0, 0.0, window_size.0, window_size.1);
+        for system in self.systems.iter_mut() {
+            let area = match system.emitter().space {
+                Space::Map => map_area,
+                Space::Screen => screen_area,
+            };
+            system.set_rate_factor(strength);
+            if !self.prewarmed {
+                system.prewarm(&mut self.rng, area);
             }
-            if drop.position.y > WINDOW_HEIGHT as f32 {
-                drop.position.y = 0.0;
-            } else if drop.position.y < 0.0 {
-                drop.position.y = WINDOW_HEIGHT as f32;
+            system.step(dt, &mut self.rng, area);
+        }
+        self.prewarmed = true;
+
+        if self.droptype == TropicalMonsoon {
+            self.flash = (self.flash - FLASH_DECAY * dt).max(0.0);
+            self.next_flash -= dt;
+            if self.next_flash <= 0.0 {
+                self.flash = self.rng.gen_range(0.5..0.9) * strength;
+                self.next_flash = self.rng.gen_range(LIGHTNING_INTERVAL.0..LIGHTNING_INTERVAL.1);
             }
         }
-        Ok(())
+    }
+    ///alpha of the fog over a tile 'distance' tiles away from the closest robot
+    pub fn fog_alpha(&self, distance: f32) -> f32 {
+        if self.droptype != Foggy {
+            return 0.0;
+        }
+        ((distance - FOG_CLEAR) / FOG_RANGE).clamp(0.0, 1.0) * FOG_MAX_ALPHA * self.strength()
+    }
+    pub fn is_snowing(&self) -> bool {
+        self.droptype == TrentinoSnow
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
-        let texture = self.droptype.get_texture(ctx);
-
-        for d in &self.drops {
-            match self.droptype {
-                Sunny => {
-                    texture
-                        .draw(ctx, DrawParams::new()
-                            .position(d.position)
-                            .scale(Vec2::new(0.5, 0.5)));
-                }
-                Foggy => {
-                    texture
-                        .draw(ctx, DrawParams::new()
-                            .position(d.position)
-                            .scale(Vec2::new(1.5, 1.5)));
-                }
-                _ => {
-                    texture
-                        .draw(ctx, DrawParams::new()
-                            .position(d.position)
-                            .scale(Vec2::new(0.1, 0.1)));
+    pub fn draw(&self, r: &mut dyn Renderer, camera: &Camera) {
+        let (width, height) = r.window_size();
+        for system in &self.systems {
+            system.draw(r, camera, self.fade);
+        }
+        match self.droptype {
+            Sunny => {
+                //heat shimmer, faint bands slowly waving over the map
+                let band_gap = (height - TOP_OFFSET) / SHIMMER_BANDS as f32;
+                for i in 0..SHIMMER_BANDS {
+                    let phase = self.time + i as f32 * 1.7;
+                    let y = TOP_OFFSET + band_gap * (i as f32 + 0.5) + (phase * 1.3).sin() * 8.0;
+                    let alpha = (0.05 + 0.03 * (phase * 2.0).sin()) * self.strength();
+                    r.draw_rect(Rectangle::new(0.0, y, width, 3.0), Color::rgba(1.0, 0.95, 0.8, alpha));
                 }
+                r.draw_sprite(Sprite::Weather(WeatherSprite::Sun), None, DrawParams::new()
+                    .position(Vec2::new(width - 60.0, 20.0))
+                    .scale(Vec2::new(0.5, 0.5))
+                    .color(Color::WHITE.with_alpha(self.fade)));
             }
+            TropicalMonsoon if self.flash > 0.0 => {
+                r.draw_rect(Rectangle::new(0.0, 0.0, width, height), Color::rgba(1.0, 1.0, 0.95, self.flash));
+            }
+            _ => {}
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,343 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Instant;
+
+use tetra::{Context, graphics, input, State, TetraError, time, window};
+use tetra::graphics::Color;
+use tetra::input::{Key, KeyModifier, MouseButton};
+
+use crate::transport::LinkStatus;
+use crate::visualizer::{DEFAULT_ROBOT, HUD_X, VisData};
+use crate::visualizer::config::VisConfig;
+use crate::visualizer::tetrarenderer::{TetraAssets, TetraRenderer};
+use crate::visualizer::textures::content_name;
+use crate::visualizer::visbackpack::VisBackPack;
+use crate::visualizer::visbackpackchart::VisBackpackChart;
+use crate::visualizer::viscamera::Camera;
+use crate::visualizer::visconsole::{ConsoleEntry, Severity, VisConsole};
+use crate::visualizer::visenergy::VisEnergy;
+use crate::visualizer::visforecast::VisForecast;
+use crate::visualizer::visinventory::VisInventoryLog;
+use crate::visualizer::vislighting;
+use crate::visualizer::vismap::VisMap;
+use crate::visualizer::visnotifications::{Notification, Notifier, VisToasts};
+use crate::visualizer::visspeech::VisSpeech;
+use crate::visualizer::visstate::{VisEvent, VisState};
+
+pub struct Visualizer {
+    style: usize,
+
+    state: VisState,
+    map: VisMap,
+    texts: VisEnergy,
+    forecast: VisForecast,
+    backpack: VisBackPack,
+    inventory_log: VisInventoryLog,
+    backpack_chart: VisBackpackChart,
+    console: VisConsole,
+    speech: VisSpeech,
+    notifier: Notifier,
+    toasts: VisToasts,
+    assets: TetraAssets,
+
+    receiver: Receiver<VisData>,
+    ///connection news of a listening visualizer, see [Visualizer::with_link_status]
+    link_status: Option<Receiver<LinkStatus>>,
+    show_backpack: bool,
+    show_speech: bool,
+    ///whether the robots' actions show an effect on the map
+    action_effects: bool,
+    show_discoverers: bool,
+    selected_robot: usize,
+    camera: Camera,
+    first: bool,
+    ///when the visualizer started, console messages are timed from here
+    started: Instant,
+}
+
+impl Visualizer {
+    pub fn new(ctx: &mut Context, size: usize, receiver: Receiver<VisData>) -> tetra::Result<Visualizer> {
+        Self::with_config(ctx, size, receiver, VisConfig::default())
+    }
+    pub fn with_config(ctx: &mut Context, size: usize, receiver: Receiver<VisData>, config: VisConfig) -> tetra::Result<Visualizer> {
+        let state = VisState::new(size);
+        let mut map = match config.weather_seed {
+            Some(seed) => VisMap::with_seed(state.weather(), seed),
+            None => VisMap::new(state.weather()),
+        };
+        map.set_weather_transition(config.weather_transition);
+        map.set_robot_move_duration(config.robot_move_duration);
+        map.set_lod_scales(config.lod_simple_below, config.lod_image_below);
+        let mut texts = VisEnergy::new();
+        texts.set_max_energy(config.max_energy);
+        Ok(
+            Self {
+                style: 0,
+                map,
+                state,
+                texts,
+                forecast: VisForecast::new(),
+                backpack: VisBackPack::new(),
+                inventory_log: VisInventoryLog::new(),
+                backpack_chart: VisBackpackChart::new(),
+                console: VisConsole::new(),
+                speech: VisSpeech::new(config.speech_duration),
+                notifier: Notifier::new(&config),
+                toasts: VisToasts::new(),
+                assets: TetraAssets::new(ctx)?,
+                receiver,
+                link_status: None,
+                show_backpack: true,
+                show_speech: config.speech_bubbles,
+                action_effects: config.action_effects,
+                show_discoverers: false,
+                selected_robot: DEFAULT_ROBOT,
+                camera: Camera::default(),
+                first : true,
+                started: Instant::now(),
+            }
+        )
+    }
+    ///applies received data to the state and reacts to what changed
+    pub(crate) fn apply(&mut self, data: VisData) {
+        if self.first {
+            self.camera.focus(data.recv_coordinates);
+            self.selected_robot = data.recv_robot_id;
+        }
+        self.first = false;
+
+        let robot_id = data.recv_robot_id;
+        let events = self.state.apply(data);
+        for notification in self.notifier.observe(robot_id, &events, &self.state) {
+            self.notify(notification);
+        }
+        //robots appear where they are, without walking from the origin
+        let mut added = None;
+        for event in events {
+            match event {
+                VisEvent::RobotAdded(robot) => { added = Some(robot); }
+                VisEvent::WeatherChanged { to, .. } => { self.map.update_weather(to); }
+                VisEvent::WeatherIntensityChanged(intensity) => { self.map.set_weather_intensity(intensity); }
+                VisEvent::TilesChanged(changed) => { self.map.tiles_changed(&self.state, &changed); }
+                VisEvent::Action { action, tile, content, .. } if self.action_effects => { self.map.action(action, tile, &content); }
+                VisEvent::RobotMoved { robot, from, to } if added != Some(robot) => { self.map.robot_moved(robot, from, to); }
+                VisEvent::BackpackChanged { robot, deltas } => {
+                    self.backpack.notify(robot, &deltas);
+                    self.inventory_log.record(self.state.step(), robot, &deltas);
+                }
+                VisEvent::Message { robot, text, severity } => {
+                    self.speech.say(robot, &text);
+                    self.console.push(ConsoleEntry {
+                        time: self.started.elapsed(),
+                        step: self.state.step(),
+                        robot: Some(robot),
+                        severity,
+                        text,
+                    }, &self.state);
+                }
+                _ => {}
+            }
+        }
+    }
+    ///writes the connection news of [crate::transport::listen_with_status] in the console
+    pub fn with_link_status(mut self, status: Receiver<LinkStatus>) -> Self {
+        self.link_status = Some(status);
+        self
+    }
+    ///writes the connection news received since the last frame in the console
+    fn report_link_status(&mut self) {
+        let news: Vec<LinkStatus> = match &self.link_status {
+            Some(status) => status.try_iter().collect(),
+            None => return,
+        };
+        for status in news {
+            let (severity, text) = match status {
+                LinkStatus::Connected => (Severity::Info, "Robot connected".to_string()),
+                LinkStatus::Disconnected => (Severity::Warning, "Robot disconnected".to_string()),
+                LinkStatus::Malformed(e) => (Severity::Warning, format!("Discarded a malformed update: {}", e)),
+                LinkStatus::AcceptFailed(e) => (Severity::Error, format!("Failed to accept a robot: {}", e)),
+            };
+            self.console.push(ConsoleEntry {
+                time: self.started.elapsed(),
+                step: self.state.step(),
+                robot: None,
+                severity,
+                text,
+            }, &self.state);
+        }
+    }
+    ///shows 'notification' as a toast and writes it to the console
+    fn notify(&mut self, notification: Notification) {
+        self.toasts.push(&notification);
+        self.console.push(ConsoleEntry {
+            time: self.started.elapsed(),
+            step: self.state.step(),
+            robot: notification.robot,
+            severity: notification.kind.severity(),
+            text: notification.text,
+        }, &self.state);
+    }
+    ///switches projection, keeping the selected robot in view
+    fn toggle_projection(&mut self) {
+        self.camera.toggle_projection();
+        if let Some(robot) = self.state.robot(self.selected_robot) {
+            self.camera.focus(robot.position());
+        }
+    }
+    ///writes what is on tile [row][col] in the console
+    fn describe_tile(&mut self, row: usize, col: usize) {
+        if let Some(tile) = self.state.tile(row, col) {
+            let text = format!("Tile ({}, {}): {:?}, {}, elevation {}",
+                               row, col, tile.tile_type, content_name(&tile.content), tile.elevation);
+            self.console.push(ConsoleEntry {
+                time: self.started.elapsed(),
+                step: self.state.step(),
+                robot: None,
+                severity: Severity::Info,
+                text,
+            }, &self.state);
+        }
+    }
+    ///selects the robot following the current one, whose panels are shown
+    fn select_next_robot(&mut self) {
+        let ids = self.state.robot_ids();
+        if let Some(i) = ids.iter().position(|id| *id == self.selected_robot) {
+            self.selected_robot = ids[(i + 1) % ids.len()];
+        } else if let Some(id) = ids.first() {
+            self.selected_robot = *id;
+        }
+    }
+    ///selects the 'n'-th robot (from 1) ordered by id, if there are that many
+    fn select_robot(&mut self, n: usize) {
+        if let Some(id) = self.state.robot_ids().get(n - 1) {
+            self.selected_robot = *id;
+        }
+    }
+}
+
+///position of the robot selected by a number key, from 1 to 9
+fn robot_number(key: Key) -> Option<usize> {
+    [Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9]
+        .iter()
+        .position(|k| *k == key)
+        .map(|i| i + 1)
+}
+
+impl State for Visualizer {
+    fn update(&mut self, ctx: &mut Context) -> Result<(), TetraError> {
+        //received data handling
+        match self.receiver.try_recv() {
+            Ok(data) => self.apply(data),
+            Err(TryRecvError::Disconnected) => {
+                if let Some(notification) = self.notifier.disconnected() {
+                    self.notify(notification);
+                }
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+        self.report_link_status();
+        let dt = time::get_delta_time(ctx).as_secs_f32();
+        self.backpack.update(dt);
+        self.speech.update(dt);
+        self.toasts.update(dt);
+        let (w, h) = window::get_size(ctx);
+        let window_size = (w as f32, h as f32);
+        self.map.update(dt, &self.camera, window_size);
+
+        //console and inventory log scrolling, the console when the mouse is over it
+        let wheel = input::get_mouse_wheel_movement(ctx).y;
+        if wheel != 0 {
+            let mouse = input::get_mouse_position(ctx);
+            if self.console.area(window_size).contains_point(mouse) {
+                self.console.scroll(wheel);
+            } else if self.inventory_log.is_visible() {
+                self.inventory_log.scroll(wheel);
+            }
+        }
+
+        //clicking a tile describes it in the console
+        if input::is_mouse_button_pressed(ctx, MouseButton::Left) {
+            let mouse = input::get_mouse_position(ctx);
+            if !self.console.area(window_size).contains_point(mouse) {
+                if let Some((row, col)) = self.map.pick(&self.state, &self.camera, mouse) {
+                    self.describe_tile(row, col);
+                }
+            }
+        }
+
+        //key input handling
+        if input::get_keys_pressed(ctx).next().is_some() {
+            let ctrl = input::is_key_modifier_down(ctx, KeyModifier::Ctrl);
+            match input::get_keys_pressed(ctx).next().unwrap() {
+                //map movement
+                Key::A | Key::Left => { self.camera.pan(1.0, 0.0); }
+                Key::D | Key::Right => { self.camera.pan(-1.0, 0.0); }
+                Key::W | Key::Up => { self.camera.pan(0.0, 1.0); }
+                Key::S | Key::Down => { self.camera.pan(0.0, -1.0); }
+                //backpack showing
+                Key::X | Key::Space => { self.show_backpack = !self.show_backpack; }
+                //robot panels and who discovered what
+                Key::Tab => { self.select_next_robot(); }
+                Key::R => { self.show_discoverers = !self.show_discoverers; }
+                //top-down or isometric map
+                Key::P => { self.toggle_projection(); }
+                //inventory log
+                Key::H => { self.inventory_log.toggle(); }
+                Key::PageUp => { self.inventory_log.scroll(1); }
+                Key::PageDown => { self.inventory_log.scroll(-1); }
+                //backpack contents over time
+                Key::G => { self.backpack_chart.toggle(); }
+                //console collapsing
+                Key::M => { self.console.toggle(); }
+                //speech bubbles
+                Key::B => { self.show_speech = !self.show_speech; }
+                //zoom
+                Key::I => { self.camera.zoom_in(); }
+                Key::O => { self.camera.zoom_out(); }
+                //style, with ctrl so the numbers are left to the robots
+                Key::Num0 if ctrl => { self.style = 0 }
+                Key::Num1 if ctrl => { self.style = 1 }
+                Key::Num2 if ctrl => { self.style = 2 }
+                Key::Num3 if ctrl => { self.style = 3 }
+                //robot selection by position, as in the web viewer
+                key => {
+                    if let Some(n) = robot_number(*key) {
+                        self.select_robot(n);
+                    }
+                }
+            }
+            if input::get_keys_down(ctx).next().is_some() {
+                match input::get_keys_down(ctx).next().unwrap() {
+                    //map movement
+                    Key::A | Key::Left => { self.camera.pan(0.25, 0.0); }
+                    Key::D | Key::Right => { self.camera.pan(-0.25, 0.0); }
+                    Key::W | Key::Up => { self.camera.pan(0.0, 0.25); }
+                    Key::S | Key::Down => { self.camera.pan(0.0, -0.25); }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> Result<(), TetraError> {
+        graphics::clear(ctx, Color::rgb(0.0, 0.0, 0.0));
+        let mut r = TetraRenderer::new(ctx, &mut self.assets);
+        self.map.draw(&mut r, &self.state, &self.camera, self.style, self.show_discoverers);
+        vislighting::draw_lighting(&mut r, self.state.time_of_day());
+        if self.show_speech {
+            self.speech.draw(&mut r, &self.state, &self.map, &self.camera);
+        }
+        self.texts.draw(&mut r, &self.state, self.selected_robot);
+        self.forecast.draw(&mut r, &self.state, HUD_X);
+        if let (true, Some(robot)) = (self.show_backpack, self.state.robot(self.selected_robot)) {
+            self.backpack.draw(&mut r, self.selected_robot, robot, (0.0, 0.0), self.style)
+        }
+        self.inventory_log.draw(&mut r, &self.state);
+        if let Some(robot) = self.state.robot(self.selected_robot) {
+            self.backpack_chart.draw(&mut r, robot, self.style);
+        }
+        self.console.draw(&mut r);
+        self.toasts.draw(&mut r);
+        Ok(())
+    }
+}
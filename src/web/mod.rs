@@ -0,0 +1,268 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use robotics_lib::world::tile::{Tile, TileType};
+use serde_json::{json, Map, Value};
+use tungstenite::{Message, WebSocket};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+
+use crate::visualizer::VisData;
+use crate::visualizer::contents::{content_name, robot_tint, Rgb, ROBOT_TINTS, tile_color};
+use crate::visualizer::visstate::{VisEvent, VisState};
+
+const VIEWER_PAGE: &str = include_str!("viewer.html");
+///updates waiting to be sent to a page, a page falling further behind is dropped
+const PAGE_QUEUE: usize = 64;
+///how long a page connection waits for incoming frames before sending the queued updates
+const POLL_TIME: Duration = Duration::from_millis(50);
+///a page taking longer to receive an update is dropped
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
+///requests with longer headers are refused
+const MAX_HEADERS: usize = 16 * 1024;
+///a client taking longer to send its request headers is dropped
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+///every tile type, for the palette sent to the pages
+const TILE_TYPES: [TileType; 11] = [
+    TileType::DeepWater, TileType::ShallowWater, TileType::Sand, TileType::Grass, TileType::Street, TileType::Hill,
+    TileType::Mountain, TileType::Snow, TileType::Lava, TileType::Teleport(false), TileType::Wall,
+];
+
+struct Shared {
+    state: VisState,
+    ///update queues of the connected pages, each sent by the page's own thread
+    pages: Vec<SyncSender<String>>,
+}
+
+///browser viewer: serves a canvas page over http and pushes the updates
+///to every connected page through a websocket on the same address
+pub struct WebViewer {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl WebViewer {
+    ///starts serving on 'addr' (e.g. "0.0.0.0:8080" to be reachable from the LAN)
+    pub fn bind(addr: impl ToSocketAddrs, size: usize) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let shared = Arc::new(Mutex::new(Shared {
+            state: VisState::new(size),
+            pages: Vec::new(),
+        }));
+
+        let accept_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&accept_shared);
+                //a failing page only loses its own connection
+                thread::spawn(move || handle_connection(stream, &shared));
+            }
+        });
+        Ok(Self { shared })
+    }
+    ///applies 'data' and queues what changed for the connected pages,
+    ///dropping the ones that are gone or too far behind
+    pub fn apply(&self, data: VisData) {
+        let mut shared = self.shared.lock().expect("web viewer state poisoned");
+        let events = shared.state.apply(data);
+        let msg = update_message(&shared.state, &events).to_string();
+        shared.pages.retain(|page| page.try_send(msg.clone()).is_ok());
+    }
+}
+
+///serves the page, or upgrades the connection if it is a websocket request
+fn handle_connection(mut stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+    stream.set_read_timeout(Some(HEAD_TIMEOUT))?;
+    stream.set_write_timeout(Some(SEND_TIMEOUT))?;
+    let (head, rest) = read_head(&mut stream)?;
+    if let Some(key) = websocket_key(&String::from_utf8_lossy(&head)) {
+        return serve_page_updates(stream, &key, rest, shared);
+    }
+
+    //plain http, whatever the path we answer with the viewer page
+    write!(stream,
+           "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           VIEWER_PAGE.len(), VIEWER_PAGE)?;
+    stream.flush()
+}
+
+///reads the request line and headers, returns them and whatever was read after them
+fn read_head(stream: &mut TcpStream) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        if let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = head.split_off(end + 4);
+            return Ok((head, rest));
+        }
+        if head.len() > MAX_HEADERS {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "request headers too long"));
+        }
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed in the request headers"));
+        }
+        head.extend_from_slice(&chunk[..n]);
+    }
+}
+
+///Sec-WebSocket-Key of a websocket upgrade request, None for any other request
+fn websocket_key(head: &str) -> Option<String> {
+    let mut upgrade = false;
+    let mut key = None;
+    for (name, value) in head.lines().skip(1).filter_map(|line| line.split_once(':')) {
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => key = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    key.filter(|_| upgrade)
+}
+
+///completes the websocket handshake, sends the whole state and then the queued updates
+///until the page closes, fails or falls behind
+fn serve_page_updates(mut stream: TcpStream, key: &str, rest: Vec<u8>, shared: &Mutex<Shared>) -> io::Result<()> {
+    write!(stream,
+           "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+           derive_accept_key(key.as_bytes()))?;
+    stream.set_read_timeout(Some(POLL_TIME))?;
+    let mut ws = WebSocket::from_partially_read(stream, rest, Role::Server, None);
+
+    //registered with the snapshot, so no update is missed or sent twice
+    let (queue, updates) = mpsc::sync_channel(PAGE_QUEUE);
+    let snapshot = {
+        let mut shared = shared.lock().expect("web viewer state poisoned");
+        shared.pages.push(queue);
+        snapshot_message(&shared.state).to_string()
+    };
+    ws.send(Message::Text(snapshot)).map_err(ws_error)?;
+    loop {
+        loop {
+            match updates.try_recv() {
+                Ok(msg) => ws.send(Message::Text(msg)).map_err(ws_error)?,
+                Err(TryRecvError::Empty) => break,
+                //dropped by [WebViewer::apply]
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+        //the page only sends close and ping frames, the pongs go out with the next flush
+        match ws.read() {
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) | Err(tungstenite::Error::AlreadyClosed) => {
+                let _ = ws.flush();
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(ws_error(e)),
+        }
+        ws.flush().map_err(ws_error)?;
+    }
+}
+
+fn ws_error(e: tungstenite::Error) -> io::Error {
+    match e {
+        tungstenite::Error::Io(e) => e,
+        e => io::Error::other(e.to_string()),
+    }
+}
+
+fn tile_json(state: &VisState, coords: (usize, usize), tile: &Tile) -> Value {
+    json!([coords.0, coords.1, tile_type_name(tile.tile_type), content_name(&tile.content),
+        state.discovered_by(coords.0, coords.1)])
+}
+
+fn robots_json(state: &VisState) -> Value {
+    let robots: Vec<Value> = state.robots().map(|(id, robot)| {
+        let backpack: Vec<Value> = robot.backpack_items().iter()
+            .map(|(c, q)| json!([content_name(c), q]))
+            .collect();
+        json!({
+            "id": id,
+            "name": robot.name(),
+            "position": robot.position(),
+            "energy": robot.energy(),
+            "backpack": backpack,
+            "backpack_used": robot.backpack_used(),
+            "backpack_size": robot.backpack_size(),
+            "rizz": robot.rizz_text(),
+        })
+    }).collect();
+    Value::Array(robots)
+}
+
+///colors of the tile types and the robots, the same as in the window and the terminal
+fn palette_json() -> Value {
+    let tiles: Map<String, Value> = TILE_TYPES.iter()
+        .map(|&t| (tile_type_name(t).to_string(), json!(css_color(tile_color(t)))))
+        .collect();
+    let robots: Vec<String> = (0..ROBOT_TINTS).map(|id| css_color(robot_tint(id))).collect();
+    json!({ "tiles": tiles, "robots": robots })
+}
+
+fn css_color(color: Rgb) -> String {
+    let [r, g, b] = color.bytes();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+///whole state, sent to pages as soon as they connect
+fn snapshot_message(state: &VisState) -> Value {
+    let tiles: Vec<Value> = state.discovered_tiles().map(|(c, t)| tile_json(state, c, t)).collect();
+    json!({
+        "type": "snapshot",
+        "size": state.world_size(),
+        "palette": palette_json(),
+        "tiles": tiles,
+        "robots": robots_json(state),
+        "weather": format!("{:?}", state.weather()),
+    })
+}
+
+///changed tiles plus the robot data, which is small enough to always send
+fn update_message(state: &VisState, events: &[VisEvent]) -> Value {
+    let mut tiles = Vec::new();
+    for e in events {
+        if let VisEvent::TilesChanged(changed) = e {
+            for &(row, col) in changed {
+                if let Some(t) = state.tile(row, col) {
+                    tiles.push(tile_json(state, (row, col), t));
+                }
+            }
+        }
+    }
+    json!({
+        "type": "update",
+        "tiles": tiles,
+        "robots": robots_json(state),
+        "weather": format!("{:?}", state.weather()),
+    })
+}
+
+fn tile_type_name(tile_type: TileType) -> &'static str {
+    match tile_type {
+        TileType::DeepWater => "DeepWater",
+        TileType::ShallowWater => "ShallowWater",
+        TileType::Sand => "Sand",
+        TileType::Grass => "Grass",
+        TileType::Street => "Street",
+        TileType::Hill => "Hill",
+        TileType::Mountain => "Mountain",
+        TileType::Snow => "Snow",
+        TileType::Lava => "Lava",
+        TileType::Teleport(_) => "Teleport",
+        TileType::Wall => "Wall",
+    }
+}
+
+///runs only the browser viewer, no window is opened
+pub fn serve(vis_receiver: Receiver<VisData>, size: usize, addr: impl ToSocketAddrs) -> io::Result<()> {
+    let viewer = WebViewer::bind(addr, size)?;
+    for data in vis_receiver.iter() {
+        viewer.apply(data);
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use visualizer_lib::visualizer::chunkmap::{ChunkMap, CHUNK_SIZE};
+use visualizer_lib::visualizer::visstate::VisState;
+use visualizer_lib::visualizer::VisData;
+
+#[test]
+fn chunks_are_allocated_on_first_write() {
+    let mut map = ChunkMap::new(1000);
+    assert_eq!(map.chunks().count(), 0);
+
+    assert_eq!(map.insert(40, 70, 'a'), None);
+    assert_eq!(map.insert(41, 70, 'b'), None);
+    assert_eq!(map.insert(41, 70, 'c'), Some('b'));
+    //outside the grid
+    assert_eq!(map.insert(1000, 0, 'd'), None);
+
+    assert_eq!(map.chunks().collect::<Vec<_>>(), vec![(32..64, 64..96)]);
+    assert_eq!(map.get(41, 70), Some(&'c'));
+    assert_eq!(map.get(42, 70), None);
+    assert_eq!(map.get(5000, 5000), None);
+}
+
+#[test]
+//a reversed range is given on purpose, it must yield nothing
+#[allow(clippy::reversed_empty_ranges)]
+fn iteration_skips_empty_chunks() {
+    let mut map = ChunkMap::new(100);
+    map.insert(99, 99, 3);
+    map.insert(0, 1, 1);
+    map.insert(CHUNK_SIZE, 0, 2);
+
+    let all: Vec<((usize, usize), &i32)> = map.iter().collect();
+    assert_eq!(all, vec![((0, 1), &1), ((CHUNK_SIZE, 0), &2), ((99, 99), &3)]);
+    let some: Vec<(usize, usize)> = map.iter_in(0..50, 0..50).map(|(c, _)| c).collect();
+    assert_eq!(some, vec![(0, 1), (CHUNK_SIZE, 0)]);
+    assert_eq!(map.iter_in(10..5, 0..100).count(), 0);
+}
+
+#[test]
+fn huge_world_allocates_only_the_discovered_region() {
+    let mut state = VisState::new(20_000);
+    let tile = Tile { tile_type: TileType::Grass, content: Content::None, elevation: 0 };
+    let view = vec![vec![Some(tile); 3]; 3];
+    state.apply(VisData::new_discover(10, (10_000, 10_000), view));
+
+    assert_eq!(state.discovered_chunks().count(), 1);
+    assert_eq!(state.discovered_tiles().count(), 9);
+    assert_eq!(state.discovered_by(9_999, 10_001), Some(0));
+}
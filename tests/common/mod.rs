@@ -0,0 +1,6 @@
+use std::path::PathBuf;
+
+///path of the snapshot 'name' in tests/golden
+pub fn golden(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.txt", name))
+}
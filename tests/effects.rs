@@ -0,0 +1,107 @@
+#![cfg(feature = "tetra")]
+
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use visualizer_lib::visualizer::recorder::{DrawCommand, RecordingRenderer};
+use visualizer_lib::visualizer::renderer::Sprite;
+use visualizer_lib::visualizer::textures::content_color;
+use visualizer_lib::visualizer::viscamera::Camera;
+use visualizer_lib::visualizer::vismap::VisMap;
+use visualizer_lib::visualizer::visstate::{VisAction, VisEvent, VisState};
+use visualizer_lib::visualizer::VisData;
+
+fn grass(content: Content) -> Option<Tile> {
+    Some(Tile { tile_type: TileType::Grass, content, elevation: 0 })
+}
+
+///3x3 view of grass with 'content' on the right of the robot and 'far' in a corner
+fn view(content: Content, far: Content) -> Vec<Vec<Option<Tile>>> {
+    let mut view = vec![vec![grass(Content::None); 3]; 3];
+    view[1][2] = grass(content);
+    view[0][0] = grass(far);
+    view
+}
+
+///actions among the events of applying 'data'
+fn actions(state: &mut VisState, data: VisData) -> Vec<(VisAction, (usize, usize), Content)> {
+    state.apply(data).into_iter().filter_map(|e| match e {
+        VisEvent::Action { action, tile, content, .. } => Some((action, tile, content)),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn actions_are_detected_from_content_changes() {
+    let cases = [
+        (Content::Tree(2), Content::None, Some(VisAction::Destroy)),
+        (Content::Rock(3), Content::Rock(1), Some(VisAction::Destroy)),
+        (Content::Coin(4), Content::None, Some(VisAction::Collect)),
+        (Content::Bin(0..5), Content::Bin(2..5), Some(VisAction::Put)),
+        (Content::Bank(1..9), Content::Bank(4..9), Some(VisAction::Put)),
+        (Content::Fire, Content::None, Some(VisAction::Extinguish)),
+        (Content::None, Content::Tree(1), None),
+        (Content::Garbage(2), Content::None, None),
+        (Content::Bin(2..5), Content::Bin(2..5), None),
+    ];
+    for (from, to, expected) in cases {
+        assert_eq!(VisAction::detect(&from, &to), expected, "{:?} -> {:?}", from, to);
+    }
+}
+
+#[test]
+fn changes_next_to_the_robot_become_actions() {
+    //a tree right of (5, 5) and a coin at (4, 4)
+    let discovered = view(Content::Tree(2), Content::Coin(1));
+    let mut state = VisState::new(10);
+    assert!(actions(&mut state, VisData::new_discover(100, (5, 5), discovered.clone())).is_empty());
+    assert_eq!(actions(&mut state, VisData::new_discover(100, (5, 5), view(Content::None, Content::None))),
+               vec![(VisAction::Collect, (4, 4), Content::Coin(1)), (VisAction::Destroy, (5, 6), Content::Tree(2))]);
+
+    //seen from (6, 6) the coin is not next to the robot
+    let mut state = VisState::new(10);
+    state.apply(VisData::new_discover(100, (5, 5), discovered));
+    state.apply(VisData::new_energy(100, (6, 6)));
+    let mut cleared = vec![vec![None; 5]; 5];
+    cleared[0][0] = grass(Content::None);
+    cleared[1][2] = grass(Content::None);
+    assert_eq!(actions(&mut state, VisData::new_discover(100, (6, 6), cleared)),
+               vec![(VisAction::Destroy, (5, 6), Content::Tree(2))]);
+}
+
+#[test]
+fn explicit_actions_replace_the_detected_ones() {
+    let mut state = VisState::new(10);
+    state.apply(VisData::new_discover(100, (5, 5), view(Content::Fire, Content::None)));
+
+    let data = VisData::new_discover(100, (5, 5), view(Content::None, Content::None))
+        .with_action(VisAction::Extinguish, (5, 6));
+    assert_eq!(actions(&mut state, data), vec![(VisAction::Extinguish, (5, 6), Content::Fire)]);
+    //nothing changes on the tile, the action is shown anyway
+    let data = VisData::new_energy(100, (5, 5)).with_action(VisAction::Put, (4, 5));
+    assert_eq!(actions(&mut state, data), vec![(VisAction::Put, (4, 5), Content::None)]);
+}
+
+#[test]
+fn effects_are_drawn_on_their_tile_until_they_end() {
+    let mut state = VisState::new(10);
+    state.apply(VisData::new_discover(100, (2, 2), view(Content::Rock(1), Content::Bin(0..3))));
+    let camera = Camera::default();
+    let mut map = VisMap::with_seed(state.weather(), 7);
+
+    map.action(VisAction::Destroy, (2, 3), &Content::Rock(1));
+    map.action(VisAction::Put, (1, 1), &Content::Bin(0..3));
+    map.update(0.1, &camera, (800.0, 800.0));
+    assert_eq!(map.effects().len(), 2);
+
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    //splinters around the middle of tile (2, 3), at (64, 153.6)
+    let splinters: Vec<(f32, f32)> = r.commands().iter().filter_map(|c| match c {
+        DrawCommand::Sprite { sprite: Sprite::Pixel, position, color, .. } if *color == content_color(&Content::Rock(1)) => Some((position.x, position.y)),
+        _ => None,
+    }).collect();
+    assert_eq!(splinters.len(), 12);
+    assert!(splinters.iter().all(|(x, y)| (x - 64.0).abs() < 30.0 && (y - 153.6).abs() < 30.0));
+
+    map.update(2.0, &camera, (800.0, 800.0));
+    assert!(map.effects().is_empty());
+}
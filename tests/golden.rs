@@ -0,0 +1,580 @@
+#![cfg(feature = "tetra")]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use tetra::math::Vec2;
+use visualizer_lib::visualizer::recorder::{assert_golden, DrawCommand, RecordingRenderer};
+use visualizer_lib::visualizer::renderer::Sprite;
+use visualizer_lib::visualizer::textures::Drawable;
+use visualizer_lib::visualizer::visbackpack::VisBackPack;
+use visualizer_lib::visualizer::visbackpackchart::VisBackpackChart;
+use visualizer_lib::visualizer::viscamera::{Camera, Projection};
+use visualizer_lib::visualizer::visconsole::{ConsoleEntry, Severity, VisConsole};
+use visualizer_lib::visualizer::visenergy::VisEnergy;
+use visualizer_lib::visualizer::visforecast::VisForecast;
+use visualizer_lib::visualizer::vislighting::{daylight_tint, draw_lighting};
+use visualizer_lib::visualizer::vismap::{Lod, MAP_LAYER, VisMap};
+use visualizer_lib::visualizer::visspeech::VisSpeech;
+use visualizer_lib::visualizer::visstate::{VisEvent, VisState};
+use visualizer_lib::visualizer::VisData;
+
+mod common;
+use common::golden;
+
+fn tile(tile_type: TileType, content: Content) -> Tile {
+    Tile { tile_type, content, elevation: 0 }
+}
+
+fn view() -> Vec<Vec<Option<Tile>>> {
+    vec![
+        vec![Some(tile(TileType::Grass, Content::None)), Some(tile(TileType::Sand, Content::Rock(1))), Some(tile(TileType::Hill, Content::None))],
+        vec![Some(tile(TileType::Street, Content::Coin(2))), Some(tile(TileType::Grass, Content::None)), Some(tile(TileType::ShallowWater, Content::Fish(1)))],
+        vec![Some(tile(TileType::Mountain, Content::Tree(3))), Some(tile(TileType::Lava, Content::Fire)), Some(tile(TileType::DeepWater, Content::None))],
+    ]
+}
+
+///state with the 3x3 view around (2, 2) discovered and sunny weather
+fn sample_state() -> VisState {
+    let mut state = VisState::new(6);
+    state.apply(VisData::new_weather(100, (2, 2), WeatherType::Sunny));
+    state.apply(VisData::new_discover(90, (2, 2), view()));
+    state
+}
+
+#[test]
+fn atlas_rects() {
+    let tiles = [TileType::DeepWater, TileType::ShallowWater, TileType::Sand, TileType::Grass, TileType::Street,
+        TileType::Hill, TileType::Mountain, TileType::Snow, TileType::Lava, TileType::Teleport(false), TileType::Wall];
+    let contents = [Content::Rock(0), Content::Tree(0), Content::Garbage(0), Content::Fire, Content::Coin(0),
+        Content::Bin(0..1), Content::Crate(0..1), Content::Bank(0..1), Content::Market(0), Content::Fish(0),
+        Content::Building, Content::Bush(0), Content::Scarecrow, Content::None];
+
+    let mut actual = String::new();
+    for t in tiles {
+        let r = t.atlas_rect();
+        actual += &format!("{:?} ({}, {}, {}, {})\n", t, r.x, r.y, r.width, r.height);
+    }
+    for c in contents {
+        let r = c.atlas_rect();
+        actual += &format!("{:?} ({}, {}, {}, {})\n", c, r.x, r.y, r.width, r.height);
+    }
+    assert_golden(golden("atlas_rects"), &actual);
+}
+
+#[test]
+fn map_frame() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &Camera::default(), 0, false);
+    assert_golden(golden("map_frame"), &r.snapshot());
+}
+
+#[test]
+fn map_culling() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let mut camera = Camera::default();
+    //row 1 ends up left of the window
+    camera.pan(-2.0, 0.0);
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &camera, 1, false);
+    assert_golden(golden("map_culling"), &r.snapshot());
+}
+
+#[test]
+fn visible_tiles_include_the_edges() {
+    let camera = Camera { map_pos: (-30.0, -10.0), ..Camera::default() };
+    //tiles are 25.6 pixels, the first row and column are cut by the window edges
+    assert_eq!(camera.visible_tiles((800.0, 800.0), 100), (1..33, 0..30));
+    assert_eq!(camera.visible_tiles((800.0, 800.0), 20), (1..20, 0..20));
+    assert_eq!(Camera::default().visible_tiles((800.0, 800.0), 100).1.start, 0);
+}
+
+#[test]
+fn partially_visible_tiles_are_drawn() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    //half of row 1 and half of column 1 are outside the map area
+    let camera = Camera { map_pos: (-38.4, -38.4), ..Camera::default() };
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &camera, 0, false);
+    let tiles: Vec<(f32, f32)> = r.commands().iter().filter_map(|c| match c {
+        DrawCommand::Sprite { sprite: Sprite::Tileset(_), position, .. } => Some((position.x, position.y)),
+        _ => None,
+    }).collect();
+    assert_eq!(tiles.len(), 9);
+    assert!((tiles[0].0 + 12.8).abs() < 0.01 && (tiles[0].1 - 51.2).abs() < 0.01);
+}
+
+#[test]
+fn iso_tiles_are_drawn_back_to_front() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let camera = Camera { map_pos: (400.0, 0.0), projection: Projection::Isometric, ..Camera::default() };
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &camera, 0, false);
+    let tops: Vec<f32> = r.commands().iter().filter_map(|c| match c {
+        DrawCommand::Sprite { sprite: Sprite::IsoTileset(0), position, .. } => Some(position.y),
+        _ => None,
+    }).collect();
+    assert_eq!(tops.len(), 9);
+    assert!(tops.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn picking_in_both_projections() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    //center of tile (2, 3)
+    assert_eq!(map.pick(&state, &Camera::default(), Vec2::new(64.0, 153.6)), Some((2, 3)));
+    assert_eq!(map.pick(&state, &Camera::default(), Vec2::new(140.0, 200.0)), None);
+
+    let camera = Camera { map_pos: (400.0, 0.0), projection: Projection::Isometric, ..Camera::default() };
+    assert_eq!(map.pick(&state, &camera, Vec2::new(374.4, 140.8)), Some((2, 3)));
+    //(3, 3) raised by a whole tile covers (2, 2), right behind it
+    assert_eq!(map.pick(&state, &camera, Vec2::new(400.0, 128.0)), Some((2, 2)));
+    let mut view = vec![vec![None; 3]; 3];
+    view[2][2] = Some(Tile { tile_type: TileType::Hill, content: Content::None, elevation: 16 });
+    state.apply(VisData::new_discover(90, (2, 2), view));
+    assert_eq!(map.pick(&state, &camera, Vec2::new(400.0, 128.0)), Some((3, 3)));
+
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert!(r.commands().iter().any(|c| matches!(c, DrawCommand::Sprite { sprite: Sprite::Diamond, .. })));
+}
+
+///passes the robot moves among 'events' to 'map', as the visualizer does
+fn animate_moves(map: &mut VisMap, events: Vec<VisEvent>) {
+    for event in events {
+        if let VisEvent::RobotMoved { robot, from, to } = event {
+            map.robot_moved(robot, from, to);
+        }
+    }
+}
+
+///position, horizontal scale and alpha of the robot sprites drawn
+fn robot_sprites(r: &RecordingRenderer) -> Vec<(f32, f32, f32, f32)> {
+    r.commands().iter().filter_map(|c| match c {
+        DrawCommand::Sprite { sprite: Sprite::Robot, position, scale, color, .. } => Some((position.x, position.y, scale.x, color.a)),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn robots_walk_to_the_next_tile_facing_it() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    map.set_robot_move_duration(Duration::from_secs(1));
+    let camera = Camera::default();
+    animate_moves(&mut map, state.apply(VisData::new_energy(90, (1, 2))));
+
+    map.update(0.5, &camera, (800.0, 800.0));
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    //halfway between rows 2 and 1, flipped around its middle to look left
+    let sprites = robot_sprites(&r);
+    assert_eq!(sprites.len(), 1);
+    let (x, y, scale_x, alpha) = sprites[0];
+    assert!((x - 64.0).abs() < 0.01 && (y - 115.2).abs() < 0.01);
+    assert!(scale_x < 0.0 && alpha == 1.0);
+
+    map.update(0.6, &camera, (800.0, 800.0));
+    assert!(!map.motion().is_moving());
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    let (x, _, scale_x, _) = robot_sprites(&r)[0];
+    assert!((x - 51.2).abs() < 0.01 && scale_x < 0.0);
+}
+
+#[test]
+fn teleports_fade_out_and_in_with_a_ring() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    map.set_robot_move_duration(Duration::from_secs(1));
+    let camera = Camera::default();
+    animate_moves(&mut map, state.apply(VisData::new_discover(90, (4, 4), view())));
+
+    map.update(0.25, &camera, (800.0, 800.0));
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    //still on the old tile, half faded
+    let (x, y, scale_x, alpha) = robot_sprites(&r)[0];
+    assert!((x - 51.2).abs() < 0.01 && (y - 115.2).abs() < 0.01);
+    assert!(scale_x > 0.0 && (alpha - 0.5).abs() < 0.01);
+    let ring = r.commands().iter().filter(|c| matches!(c, DrawCommand::Sprite { sprite: Sprite::Pixel, rotation, .. } if *rotation != 0.0)).count();
+    assert!(ring >= 3);
+
+    map.update(0.5, &camera, (800.0, 800.0));
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    let (x, y, _, alpha) = robot_sprites(&r)[0];
+    assert!((x - 102.4).abs() < 0.01 && (y - 166.4).abs() < 0.01);
+    assert!((alpha - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn map_simple_lod() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let camera = Camera { scale: 0.15, ..Camera::default() };
+    assert_eq!(map.lod(camera.scale), Lod::Simple);
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert_golden(golden("map_simple_lod"), &r.snapshot());
+}
+
+#[test]
+fn map_image_lod() {
+    let state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let discovered: Vec<(usize, usize)> = state.discovered_tiles().map(|(c, _)| c).collect();
+    map.tiles_changed(&state, &discovered);
+    let camera = Camera { scale: 0.05, ..Camera::default() };
+    assert_eq!(map.lod(camera.scale), Lod::Image);
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert_golden(golden("map_image_lod"), &r.snapshot());
+}
+
+#[test]
+fn map_images_follow_the_chunks() {
+    let mut state = VisState::new(100);
+    state.apply(VisData::new_discover(100, (1, 1), view()));
+    state.apply(VisData::new_discover(100, (97, 97), view()));
+    let mut map = VisMap::new(state.weather());
+    let camera = Camera { scale: 0.02, ..Camera::default() };
+    let images = |r: &RecordingRenderer| -> Vec<(usize, usize, u64)> {
+        r.commands().iter().filter_map(|c| match c {
+            DrawCommand::Image { width, height, version, .. } => Some((*width, *height, *version)),
+            _ => None,
+        }).collect()
+    };
+
+    //only the discovered chunks, the last one cut by the world's edge
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert_eq!(images(&r), vec![(32, 32, 9), (4, 4, 9)]);
+
+    let mut view = vec![vec![None; 3]; 3];
+    view[1][1] = Some(tile(TileType::Snow, Content::None));
+    for event in state.apply(VisData::new_discover(100, (1, 1), view)) {
+        if let VisEvent::TilesChanged(changed) = event {
+            map.tiles_changed(&state, &changed);
+        }
+    }
+    let mut r = RecordingRenderer::default();
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert_eq!(images(&r), vec![(32, 32, 10), (4, 4, 9)]);
+}
+
+#[test]
+fn map_layer_redraws_only_changed_tiles() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let mut camera = Camera::default();
+    let mut r = RecordingRenderer::default().with_layers();
+    let tiles = |frame: &[DrawCommand]| frame.iter()
+        .filter(|c| matches!(c, DrawCommand::Sprite { sprite: Sprite::Tileset(_), .. }))
+        .count();
+
+    map.draw(&mut r, &state, &camera, 0, false);
+    let frame = r.take_frame();
+    assert_eq!(frame[0], DrawCommand::BeginLayer { id: MAP_LAYER, clear: true });
+    assert_eq!(tiles(&frame), 9);
+
+    //nothing changed, the layer is only composited
+    map.draw(&mut r, &state, &camera, 0, false);
+    let frame = r.take_frame();
+    assert_eq!(frame[0], DrawCommand::Layer(MAP_LAYER));
+    assert_eq!(tiles(&frame), 0);
+
+    let mut view = vec![vec![None; 3]; 3];
+    view[1][1] = Some(tile(TileType::Snow, Content::None));
+    for event in state.apply(VisData::new_discover(90, (2, 2), view)) {
+        if let VisEvent::TilesChanged(changed) = event {
+            map.tiles_changed(&state, &changed);
+        }
+    }
+    map.draw(&mut r, &state, &camera, 0, false);
+    let frame = r.take_frame();
+    assert_eq!(frame[0], DrawCommand::BeginLayer { id: MAP_LAYER, clear: false });
+    assert_eq!(tiles(&frame), 1);
+    assert_eq!(frame[3], DrawCommand::EndLayer);
+    assert_eq!(frame[4], DrawCommand::Layer(MAP_LAYER));
+
+    //moving the camera draws the whole layer again
+    camera.pan(1.0, 0.0);
+    map.draw(&mut r, &state, &camera, 0, false);
+    let frame = r.take_frame();
+    assert_eq!(frame[0], DrawCommand::BeginLayer { id: MAP_LAYER, clear: true });
+    assert_eq!(tiles(&frame), 9);
+}
+
+///discovers a 'tile_type' tile under the robot of 'state', telling 'map'
+fn change_tile(state: &mut VisState, map: &mut VisMap, tile_type: TileType) {
+    let mut view = vec![vec![None; 3]; 3];
+    view[1][1] = Some(tile(tile_type, Content::None));
+    for event in state.apply(VisData::new_discover(90, (2, 2), view)) {
+        if let VisEvent::TilesChanged(changed) = event {
+            map.tiles_changed(state, &changed);
+        }
+    }
+}
+
+#[test]
+fn changed_tiles_are_kept_only_for_a_layer() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    let camera = Camera::default();
+    let far = Camera { scale: 0.05, ..Camera::default() };
+
+    //zoomed out the map is drawn as images, not in the layer
+    let mut r = RecordingRenderer::default().with_layers();
+    map.draw(&mut r, &state, &camera, 0, false);
+    for tile_type in [TileType::Snow, TileType::Sand, TileType::Lava] {
+        map.draw(&mut r, &state, &far, 0, false);
+        change_tile(&mut state, &mut map, tile_type);
+        assert!(map.pending_tiles().is_empty());
+    }
+    //back in, the layer is drawn again whole
+    r.take_frame();
+    map.draw(&mut r, &state, &camera, 0, false);
+    assert_eq!(r.take_frame()[0], DrawCommand::BeginLayer { id: MAP_LAYER, clear: true });
+
+    //a renderer without layers
+    let mut r = RecordingRenderer::default();
+    for tile_type in [TileType::Snow, TileType::Sand, TileType::Lava] {
+        map.draw(&mut r, &state, &camera, 0, false);
+        change_tile(&mut state, &mut map, tile_type);
+        assert!(map.pending_tiles().is_empty());
+    }
+}
+
+#[test]
+fn hud_frame() {
+    let mut state = sample_state();
+    let backpack = HashMap::from([(Content::Tree(0), 2), (Content::Rock(0), 3), (Content::Coin(0), 0)]);
+    state.apply(VisData::new_backpack(80, (2, 2), backpack).with_backpack_size(6));
+    state.apply(VisData::new_rizzler(80, (2, 2), "collecting rocks".to_string()));
+    let mut r = RecordingRenderer::default();
+
+    VisEnergy::new().draw(&mut r, &state, 0);
+    VisBackPack::new().draw(&mut r, 0, state.robot(0).unwrap(), (0.0, 0.0), 0);
+    assert_golden(golden("hud_frame"), &r.snapshot());
+}
+
+#[test]
+fn energy_bar_follows_the_max_energy() {
+    let state = sample_state();
+    let mut energy = VisEnergy::new();
+    energy.set_max_energy(100);
+    let mut r = RecordingRenderer::default();
+
+    energy.draw(&mut r, &state, 0);
+    //90 out of 100 fills 9/10 of the bar
+    assert!(r.commands().iter().any(|c| matches!(c,
+        DrawCommand::Sprite { sprite: Sprite::Pixel, scale, .. } if (scale.x - 144.0).abs() < 0.01)));
+}
+
+#[test]
+fn backpack_wraps_in_narrow_window() {
+    let mut state = sample_state();
+    let backpack = HashMap::from([(Content::Coin(0), 4), (Content::Rock(0), 1), (Content::Tree(0), 2)]);
+    state.apply(VisData::new_backpack(80, (2, 2), backpack).with_backpack_size(8));
+    //room for two slots per row
+    let mut r = RecordingRenderer::new((150.0, 800.0));
+
+    VisBackPack::new().draw(&mut r, 0, state.robot(0).unwrap(), (0.0, 0.0), 0);
+    assert_golden(golden("backpack_narrow"), &r.snapshot());
+}
+
+#[test]
+fn two_robots_frame() {
+    let mut state = sample_state();
+    state.apply(VisData::new_discover(70, (4, 4), view()).with_robot(1).with_robot_name("Rex"));
+    let mut map = VisMap::new(state.weather());
+    let mut r = RecordingRenderer::default();
+
+    map.draw(&mut r, &state, &Camera::default(), 0, true);
+    VisEnergy::new().draw(&mut r, &state, 1);
+    assert_golden(golden("two_robots_frame"), &r.snapshot());
+}
+
+#[test]
+fn update_map_at_the_corner() {
+    let mut state = VisState::new(4);
+    state.apply(VisData::new_discover(0, (0, 0), view()));
+
+    let discovered: Vec<(usize, usize)> = state.discovered_tiles().map(|(c, _)| c).collect();
+    assert_eq!(discovered, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(state.tile(0, 0).unwrap().tile_type, TileType::Grass);
+}
+
+#[test]
+fn backpack_change_frame() {
+    let mut state = sample_state();
+    state.apply(VisData::new_backpack(80, (2, 2), HashMap::from([(Content::Rock(0), 1), (Content::Coin(0), 2)])));
+    let events = state.apply(VisData::new_backpack(80, (2, 2), HashMap::from([(Content::Rock(0), 4)])));
+    let deltas = vec![(Content::Rock(0), 3), (Content::Coin(0), -2)];
+    assert!(events.contains(&VisEvent::BackpackChanged { robot: 0, deltas: deltas.clone() }));
+
+    let mut backpack = VisBackPack::new();
+    backpack.notify(0, &deltas);
+    backpack.update(0.75);
+    let mut r = RecordingRenderer::default();
+    backpack.draw(&mut r, 0, state.robot(0).unwrap(), (0.0, 0.0), 0);
+    assert_golden(golden("backpack_change"), &r.snapshot());
+}
+
+#[test]
+fn backpack_chart_frame() {
+    let mut state = sample_state();
+    state.apply(VisData::new_backpack(80, (2, 2), HashMap::from([(Content::Rock(0), 1)])));
+    state.apply(VisData::new_backpack(80, (2, 2), HashMap::from([(Content::Rock(0), 2), (Content::Tree(0), 1)])));
+    state.apply(VisData::new_backpack(80, (2, 2), HashMap::from([(Content::Tree(0), 3)])));
+    let steps: Vec<usize> = state.robot(0).unwrap().backpack_history().iter().map(|(s, _)| *s).collect();
+    assert_eq!(steps, vec![3, 4, 5]);
+
+    let mut chart = VisBackpackChart::new();
+    chart.toggle();
+    let mut r = RecordingRenderer::default();
+    chart.draw(&mut r, state.robot(0).unwrap(), 0);
+    assert_golden(golden("backpack_chart"), &r.snapshot());
+}
+
+#[test]
+fn console_frame() {
+    let mut state = sample_state();
+    let mut console = VisConsole::new();
+    let messages = [
+        ("heading to the bank", Severity::Info),
+        ("low on energy, this message is long enough to be wrapped on more lines", Severity::Warning),
+        ("stuck", Severity::Error),
+    ];
+    for (i, (text, severity)) in messages.into_iter().enumerate() {
+        let data = VisData::new_rizzler(80, (2, 2), text.to_string()).with_severity(severity);
+        for event in state.apply(data) {
+            if let VisEvent::Message { robot, text, severity } = event {
+                console.push(ConsoleEntry { time: Duration::from_secs(61 + i as u64), step: state.step(), robot: Some(robot), severity, text }, &state);
+            }
+        }
+    }
+    let mut r = RecordingRenderer::default();
+    console.draw(&mut r);
+    console.toggle();
+    console.draw(&mut r);
+    assert_golden(golden("console_frame"), &r.snapshot());
+}
+
+#[test]
+fn console_scrolling_stays_on_the_lines_read() {
+    let state = sample_state();
+    let mut console = VisConsole::new();
+    let entry = |i: u64| ConsoleEntry { time: Duration::from_secs(i), step: 0, robot: None, severity: Severity::Info, text: format!("message {}", i) };
+    for i in 0..10 {
+        console.push(entry(i), &state);
+    }
+    //8 of the 10 lines are visible
+    console.scroll(100);
+    assert_eq!(console.scrolled(), 2);
+    console.push(entry(10), &state);
+    assert_eq!(console.scrolled(), 3);
+    console.scroll(-100);
+    assert_eq!(console.scrolled(), 0);
+    console.push(entry(11), &state);
+    assert_eq!(console.scrolled(), 0);
+}
+
+#[test]
+fn speech_bubble_frame() {
+    let state = sample_state();
+    let map = VisMap::new(state.weather());
+    let mut speech = VisSpeech::new(Duration::from_secs(4));
+    speech.say(0, "going to the bank with the coins");
+    let mut r = RecordingRenderer::default();
+    speech.draw(&mut r, &state, &map, &Camera::default());
+    //fading out at the end of its life
+    speech.update(3.6);
+    speech.draw(&mut r, &state, &map, &Camera::default());
+    speech.update(0.5);
+    speech.draw(&mut r, &state, &map, &Camera::default());
+    assert_golden(golden("speech_bubble"), &r.snapshot());
+}
+
+///top left corner of the tail of the speech bubble drawn
+fn bubble_tail(r: &RecordingRenderer) -> (f32, f32) {
+    r.commands().iter().find_map(|c| match c {
+        DrawCommand::Sprite { sprite: Sprite::Pixel, position, scale, .. } if scale.x == 6.0 => Some((position.x, position.y)),
+        _ => None,
+    }).expect("no bubble drawn")
+}
+
+#[test]
+fn speech_bubbles_follow_the_drawn_robot() {
+    let mut state = sample_state();
+    let mut map = VisMap::new(state.weather());
+    map.set_robot_move_duration(Duration::from_secs(1));
+    let mut speech = VisSpeech::new(Duration::from_secs(4));
+    speech.say(0, "on my way");
+    let mut camera = Camera::default();
+    let tile_size = camera.tile_size();
+
+    //halfway between rows 2 and 1, not on the tile it walks to
+    animate_moves(&mut map, state.apply(VisData::new_energy(90, (1, 2))));
+    map.update(0.5, &camera, (800.0, 800.0));
+    let mut r = RecordingRenderer::default();
+    speech.draw(&mut r, &state, &map, &camera);
+    let (x, y) = bubble_tail(&r);
+    assert!((x - (38.4 + tile_size / 2.0 - 3.0)).abs() < 0.01 && (y - (115.2 - 8.0)).abs() < 0.01);
+
+    //isometric, standing on a raised tile
+    map.update(1.0, &camera, (800.0, 800.0));
+    let mut view = vec![vec![None; 3]; 3];
+    view[1][1] = Some(Tile { elevation: 4, ..tile(TileType::Hill, Content::None) });
+    state.apply(VisData::new_discover(90, (1, 2), view));
+    camera.projection = Projection::Isometric;
+    let (x_pixel, y_pixel) = camera.tile_to_screen((1, 2));
+    let top = y_pixel - tile_size / 2.0 - camera.elevation_lift(4);
+    assert_eq!(map.robot_screen_pos(&state, 0, &camera), Some((x_pixel, top)));
+    let mut r = RecordingRenderer::default();
+    speech.draw(&mut r, &state, &map, &camera);
+    let (x, y) = bubble_tail(&r);
+    assert!((x - (x_pixel + tile_size / 2.0 - 3.0)).abs() < 0.01 && (y - (top - 8.0)).abs() < 0.01);
+}
+
+#[test]
+fn day_night_lighting() {
+    assert_eq!(daylight_tint(12, 0).a, 0.0);
+    assert!(daylight_tint(23, 0).a > daylight_tint(19, 0).a);
+    //halfway between dawn and day
+    assert!((daylight_tint(7, 15).a - 0.125).abs() < 1e-4);
+
+    let mut state = sample_state();
+    state.apply(VisData::new_energy(80, (2, 2)).with_time_of_day(19, 5));
+    let mut r = RecordingRenderer::default();
+    draw_lighting(&mut r, state.time_of_day());
+    VisEnergy::new().draw(&mut r, &state, 0);
+    assert_golden(golden("night_hud"), &r.snapshot());
+}
+
+#[test]
+fn forecast_strip() {
+    let mut state = sample_state();
+    let forecast = vec![(14, WeatherType::Sunny), (16, WeatherType::TropicalMonsoon), (19, WeatherType::Foggy),
+                        (21, WeatherType::TrentinoSnow), (23, WeatherType::Rainy), (2, WeatherType::Sunny)];
+    state.apply(VisData::new_energy(80, (2, 2)).with_forecast(forecast));
+    //only five entries fit on the right of the window
+    let mut r = RecordingRenderer::default();
+    VisForecast::new().draw(&mut r, &state, 614.4);
+    assert_golden(golden("forecast_strip"), &r.snapshot());
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "tetra")]
+
+use std::collections::HashMap;
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::Content;
+use visualizer_lib::visualizer::config::VisConfig;
+use visualizer_lib::visualizer::visnotifications::{NotificationKind, Notifier};
+use visualizer_lib::visualizer::visstate::VisState;
+use visualizer_lib::visualizer::VisData;
+
+///applies 'data' and returns the kinds of the notifications it raised
+fn observe(state: &mut VisState, notifier: &mut Notifier, data: VisData) -> Vec<NotificationKind> {
+    let robot = data.recv_robot_id;
+    let events = state.apply(data);
+    notifier.observe(robot, &events, state).into_iter().map(|n| n.kind).collect()
+}
+
+#[test]
+fn low_energy_is_notified_when_crossing_the_threshold() {
+    let mut state = VisState::new(4);
+    let mut notifier = Notifier::new(&VisConfig::default().with_low_energy_threshold(100));
+
+    assert!(observe(&mut state, &mut notifier, VisData::new_energy(150, (1, 1))).is_empty());
+    assert_eq!(observe(&mut state, &mut notifier, VisData::new_energy(90, (1, 2))), vec![NotificationKind::LowEnergy]);
+    assert!(observe(&mut state, &mut notifier, VisData::new_energy(80, (1, 1))).is_empty());
+
+    //a robot already low when it is first seen
+    assert_eq!(observe(&mut state, &mut notifier, VisData::new_energy(50, (2, 2)).with_robot(1)),
+               vec![NotificationKind::LowEnergy]);
+}
+
+#[test]
+fn stuck_counts_the_steps_of_the_world() {
+    let mut state = VisState::new(4);
+    let config = VisConfig::default().with_stuck_steps(2).with_low_energy_threshold(0);
+    let mut notifier = Notifier::new(&config);
+    let at = |hour: u8, minute: u8| VisData::new_energy(500, (1, 1)).with_time_of_day(hour, minute);
+
+    assert!(observe(&mut state, &mut notifier, at(8, 0)).is_empty());
+    //more updates in the same step
+    assert!(observe(&mut state, &mut notifier, at(8, 0)).is_empty());
+    assert!(observe(&mut state, &mut notifier, at(8, 0)).is_empty());
+    assert_eq!(observe(&mut state, &mut notifier, at(8, 1)), vec![NotificationKind::Stuck]);
+    assert!(observe(&mut state, &mut notifier, at(8, 1)).is_empty());
+}
+
+#[test]
+fn stuck_backpack_full_and_weather() {
+    let mut state = VisState::new(4);
+    let config = VisConfig::default().with_stuck_steps(3).with_low_energy_threshold(0);
+    let mut notifier = Notifier::new(&config);
+
+    assert!(observe(&mut state, &mut notifier, VisData::new_energy(500, (1, 1))).is_empty());
+    let full = HashMap::from([(Content::Rock(0), 2)]);
+    assert_eq!(observe(&mut state, &mut notifier, VisData::new_backpack(500, (1, 1), full).with_backpack_size(2)),
+               vec![NotificationKind::BackpackFull]);
+    assert_eq!(observe(&mut state, &mut notifier, VisData::new_weather(500, (1, 1), WeatherType::Foggy)),
+               vec![NotificationKind::WeatherChanged, NotificationKind::Stuck]);
+    assert!(observe(&mut state, &mut notifier, VisData::new_energy(500, (1, 1))).is_empty());
+}
+
+#[test]
+fn disabled_notifications_are_filtered() {
+    let mut state = VisState::new(4);
+    let config = VisConfig::default()
+        .with_notification(NotificationKind::WeatherChanged, false)
+        .with_notification(NotificationKind::Disconnected, false);
+    let mut notifier = Notifier::new(&config);
+
+    assert!(observe(&mut state, &mut notifier, VisData::new_weather(500, (1, 1), WeatherType::Foggy)).is_empty());
+    assert!(notifier.disconnected().is_none());
+}
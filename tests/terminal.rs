@@ -0,0 +1,29 @@
+#![cfg(feature = "terminal")]
+
+use crossterm::style::Color;
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use visualizer_lib::visualizer::visstate::VisState;
+use visualizer_lib::visualizer::visterminal::{map_cell, TermCell};
+use visualizer_lib::visualizer::VisData;
+
+fn street(content: Content) -> Option<Tile> {
+    Some(Tile { tile_type: TileType::Street, content, elevation: 0 })
+}
+
+#[test]
+fn cells_follow_the_window_palette() {
+    let mut state = VisState::new(4);
+    let mut view = vec![vec![street(Content::None); 3]; 3];
+    view[0][0] = street(Content::Tree(1));
+    state.apply(VisData::new_discover(100, (1, 1), view));
+    state.apply(VisData::new_energy(100, (2, 1)).with_robot(1));
+
+    let background = Color::Rgb { r: 115, g: 115, b: 115 };
+    assert_eq!(map_cell(&state, (1, 1)),
+               Some(TermCell { glyph: "@0".to_string(), foreground: Color::Rgb { r: 255, g: 255, b: 255 }, background }));
+    assert_eq!(map_cell(&state, (2, 1)),
+               Some(TermCell { glyph: "@1".to_string(), foreground: Color::Rgb { r: 255, g: 115, b: 115 }, background }));
+    assert_eq!(map_cell(&state, (0, 0)),
+               Some(TermCell { glyph: "♣ ".to_string(), foreground: Color::White, background }));
+    assert_eq!(map_cell(&state, (3, 3)), None);
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use robotics_lib::world::environmental_conditions::WeatherType;
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use visualizer_lib::transport::{listen, listen_with_status, Endpoint, LinkStatus, VisSender};
+use visualizer_lib::transport::wire::WireData;
+use visualizer_lib::visualizer::VisData;
+use visualizer_lib::visualizer::visstate::VisAction;
+
+fn sample() -> VisData {
+    let tile = Tile { tile_type: TileType::Teleport(true), content: Content::Bank(0..5), elevation: 3 };
+    VisData::new(
+        42,
+        (3, 4),
+        Some(vec![vec![None, Some(tile)]]),
+        Some(HashMap::from([(Content::Coin(0), 7)])),
+        Some(WeatherType::TropicalMonsoon),
+        Some("hello".to_string()),
+    ).with_time_of_day(18, 30)
+        .with_forecast(vec![(20, WeatherType::Rainy), (23, WeatherType::Sunny)])
+        .with_weather_intensity(0.5)
+        .with_action(VisAction::Put, (3, 5))
+}
+
+#[test]
+fn wire_round_trip() {
+    let wire = WireData::from(&sample());
+    let line = wire.to_line().unwrap();
+    assert!(!line.contains('\n'));
+
+    let back = VisData::from(WireData::from_line(&line).unwrap());
+    assert_eq!(WireData::from(&back), wire);
+}
+
+#[test]
+fn listener_accepts_a_restarted_robot() {
+    //grab a free port
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let rx = listen(Endpoint::parse(&addr)).unwrap();
+
+    let mut first = VisSender::connect(Endpoint::parse(&addr)).unwrap();
+    first.send(&VisData::new_energy(1, (0, 0))).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().recv_energy, 1);
+    drop(first);
+
+    let mut second = VisSender::new(Endpoint::parse(&addr));
+    second.send(&VisData::new_energy(2, (0, 0))).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().recv_energy, 2);
+}
+
+#[test]
+fn robots_are_read_while_others_stay_connected() {
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let rx = listen(Endpoint::parse(&addr)).unwrap();
+
+    let mut first = VisSender::connect(Endpoint::parse(&addr)).unwrap();
+    first.send(&VisData::new_energy(1, (0, 0)).with_robot(1)).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().recv_robot_id, 1);
+
+    let mut second = VisSender::connect(Endpoint::parse(&addr)).unwrap();
+    second.send(&VisData::new_energy(2, (0, 0)).with_robot(2)).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().recv_robot_id, 2);
+    first.send(&VisData::new_energy(3, (0, 0)).with_robot(1)).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().recv_energy, 3);
+}
+
+#[test]
+fn connection_news_are_reported() {
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let (_rx, status) = listen_with_status(Endpoint::parse(&addr)).unwrap();
+
+    let mut robot = TcpStream::connect(&addr).unwrap();
+    robot.write_all(b"not an update\n").unwrap();
+    drop(robot);
+    let wait = || status.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(wait(), LinkStatus::Connected);
+    assert!(matches!(wait(), LinkStatus::Malformed(_)));
+    assert_eq!(wait(), LinkStatus::Disconnected);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_listener_keeps_other_files() {
+    let path = std::env::temp_dir().join(format!("visualizer_lib_not_a_socket_{}", std::process::id()));
+    std::fs::write(&path, "keep me").unwrap();
+
+    assert!(listen(Endpoint::Unix(path.clone())).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    std::fs::remove_file(&path).unwrap();
+}
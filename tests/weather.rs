@@ -0,0 +1,148 @@
+#![cfg(feature = "tetra")]
+
+use std::time::Duration;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use robotics_lib::world::environmental_conditions::WeatherType;
+use tetra::graphics::{Color, Rectangle};
+use tetra::math::Vec2;
+use visualizer_lib::visualizer::particles::{Emitter, ParticleSystem, Space, Spawn};
+use visualizer_lib::visualizer::recorder::{assert_golden, RecordingRenderer};
+use visualizer_lib::visualizer::renderer::Sprite;
+use visualizer_lib::visualizer::viscamera::Camera;
+use visualizer_lib::visualizer::vismap::VisMap;
+use visualizer_lib::visualizer::visweather::VisWeather;
+
+mod common;
+use common::golden;
+
+fn emitter() -> Emitter {
+    Emitter {
+        sprite: Sprite::Pixel,
+        space: Space::Map,
+        spawn: Spawn::Top,
+        rate: 8.0,
+        max: 8,
+        velocity: Vec2::new(0.0, 100.0),
+        jitter: Vec2::new(0.0, 0.0),
+        lifetime: 0.5,
+        scale: Vec2::new(1.0, 1.0),
+        color: Color::WHITE,
+    }
+}
+
+#[test]
+fn particles_spawn_move_and_die() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let area = Rectangle::new(0.0, 0.0, 1000.0, 1000.0);
+    let mut system = ParticleSystem::new(emitter());
+
+    system.step(0.25, &mut rng, area);
+    assert_eq!(system.particles().len(), 2);
+    assert!(system.particles().iter().all(|p| p.position.y == 0.0 && p.age == 0.0));
+
+    system.step(0.25, &mut rng, area);
+    assert_eq!(system.particles().len(), 4);
+    assert!(system.particles()[..2].iter().all(|p| p.position.y == 25.0));
+
+    //particles live two steps, so only the last two batches are left
+    for _ in 0..4 {
+        system.step(0.25, &mut rng, area);
+    }
+    assert_eq!(system.particles().len(), 4);
+    assert!(system.particles().iter().all(|p| p.age < 0.5));
+}
+
+#[test]
+fn particles_leaving_the_area_die() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut system = ParticleSystem::new(Emitter { lifetime: 100.0, velocity: Vec2::new(0.0, 1000.0), ..emitter() });
+    let area = Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    system.step(0.125, &mut rng, area);
+    assert_eq!(system.particles().len(), 1);
+    system.step(0.3, &mut rng, area);
+    assert!(system.particles().iter().all(|p| p.position.y < 200.0));
+}
+
+fn positions(weather: &VisWeather) -> Vec<(f32, f32)> {
+    weather.systems().iter().flat_map(|s| s.particles().iter().map(|p| (p.position.x, p.position.y))).collect()
+}
+
+#[test]
+fn weather_does_not_depend_on_frame_rate() {
+    let camera = Camera::default();
+    let window = (800.0, 800.0);
+    let mut slow = VisWeather::new(WeatherType::Rainy, 42);
+    let mut fast = VisWeather::new(WeatherType::Rainy, 42);
+    for _ in 0..30 {
+        slow.update(1.0 / 30.0, &camera, window);
+    }
+    for _ in 0..60 {
+        fast.step(&camera, window);
+    }
+    assert!(!positions(&slow).is_empty());
+    assert_eq!(positions(&slow), positions(&fast));
+}
+
+#[test]
+fn fog_frame() {
+    let camera = Camera::default();
+    let mut weather = VisWeather::new(WeatherType::Foggy, 3);
+    for _ in 0..10 {
+        weather.step(&camera, (200.0, 200.0));
+    }
+    let mut r = RecordingRenderer::new((200.0, 200.0));
+    weather.draw(&mut r, &camera);
+    assert_golden(golden("fog_frame"), &r.snapshot());
+}
+
+#[test]
+fn weather_intensity_scales_the_particles() {
+    let camera = Camera::default();
+    let window = (800.0, 800.0);
+    let mut full = VisWeather::new(WeatherType::Rainy, 42);
+    let mut light = VisWeather::new(WeatherType::Rainy, 42);
+    light.set_intensity(0.25);
+    for _ in 0..60 {
+        full.step(&camera, window);
+        light.step(&camera, window);
+    }
+    assert!(positions(&light).len() * 2 < positions(&full).len());
+}
+
+#[test]
+fn weather_changes_cross_fade() {
+    let camera = Camera::default();
+    let window = (800.0, 800.0);
+    let mut map = VisMap::with_seed(WeatherType::Rainy, 5);
+    map.set_weather_transition(Duration::from_secs(1));
+    map.update(1.0 / 60.0, &camera, window);
+    map.update_weather(WeatherType::TrentinoSnow);
+
+    //half way the rain is still draining while the snow ramps up
+    for _ in 0..30 {
+        map.update(1.0 / 60.0, &camera, window);
+    }
+    let rain = &map.fading_weathers()[0];
+    assert_eq!(rain.droptype, WeatherType::Rainy);
+    assert!((rain.fade() - 0.5).abs() < 0.05);
+    assert!((map.weather().fade() - 0.5).abs() < 0.05);
+    assert!(!positions(map.weather()).is_empty());
+
+    for _ in 0..31 {
+        map.update(1.0 / 60.0, &camera, window);
+    }
+    assert!(map.fading_weathers().is_empty());
+    assert_eq!(map.weather().fade(), 1.0);
+}
+
+#[test]
+fn zero_transition_switches_instantly() {
+    let mut map = VisMap::with_seed(WeatherType::Rainy, 5);
+    map.set_weather_transition(Duration::ZERO);
+    map.update_weather(WeatherType::Foggy);
+    assert!(map.fading_weathers().is_empty());
+    assert_eq!(map.weather().fade(), 1.0);
+}
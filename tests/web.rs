@@ -0,0 +1,86 @@
+#![cfg(feature = "web")]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use serde_json::Value;
+use tungstenite::{Message, WebSocket};
+use visualizer_lib::visualizer::contents::tile_color;
+use visualizer_lib::visualizer::VisData;
+use visualizer_lib::web::WebViewer;
+
+fn grass() -> Option<Tile> {
+    Some(Tile { tile_type: TileType::Grass, content: Content::None, elevation: 0 })
+}
+
+///viewer of a [size x size] world on a free port, with its address
+fn viewer(size: usize) -> (WebViewer, String) {
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    (WebViewer::bind(&addr, size).unwrap(), addr)
+}
+
+///sends 'request' and reads the response head
+fn response_head(addr: &str, request: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+        head.push(byte[0]);
+    }
+    String::from_utf8(head).unwrap()
+}
+
+fn next_json(ws: &mut WebSocket<TcpStream>) -> Value {
+    match ws.read().unwrap() {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("expected a text frame, got {:?}", other),
+    }
+}
+
+#[test]
+fn websocket_key_follows_rfc_6455() {
+    let (_viewer, addr) = viewer(4);
+    //the sample handshake of the RFC, header names in any case
+    let head = response_head(&addr, "GET /ws HTTP/1.1\r\nHost: localhost\r\nupgrade: WebSocket\r\nConnection: Upgrade\r\n\
+                                     SEC-WEBSOCKET-KEY: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 101 "), "{}", head);
+    assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"), "{}", head);
+
+    //a key without the upgrade is a plain request for the page
+    let head = response_head(&addr, "GET / HTTP/1.1\r\nHost: localhost\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+    assert!(head.contains("Content-Type: text/html"), "{}", head);
+}
+
+#[test]
+fn pages_get_a_snapshot_and_then_the_updates() {
+    let (viewer, addr) = viewer(4);
+    viewer.apply(VisData::new_discover(100, (1, 1), vec![vec![grass(); 3]; 3]));
+
+    let stream = TcpStream::connect(&addr).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let (mut ws, _) = tungstenite::client(format!("ws://{}/ws", addr), stream).unwrap();
+
+    let snapshot = next_json(&mut ws);
+    assert_eq!(snapshot["type"], "snapshot");
+    assert_eq!(snapshot["size"], 4);
+    assert_eq!(snapshot["tiles"].as_array().unwrap().len(), 9);
+    assert_eq!(snapshot["robots"][0]["position"], serde_json::json!([1, 1]));
+    //the palette of the window
+    let [r, g, b] = tile_color(TileType::Grass).bytes();
+    assert_eq!(snapshot["palette"]["tiles"]["Grass"], format!("#{:02x}{:02x}{:02x}", r, g, b));
+    assert_eq!(snapshot["palette"]["robots"][0], "#ffffff");
+
+    let mut view = vec![vec![None; 3]; 3];
+    view[0][0] = Some(Tile { tile_type: TileType::Sand, content: Content::Coin(2), elevation: 0 });
+    viewer.apply(VisData::new_discover(80, (1, 1), view));
+    let update = next_json(&mut ws);
+    assert_eq!(update["type"], "update");
+    assert_eq!(update["tiles"], serde_json::json!([[0, 0, "Sand", "Coin", 0]]));
+    assert_eq!(update["robots"][0]["energy"], 80);
+    assert!(update.get("palette").is_none());
+}